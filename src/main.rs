@@ -1,8 +1,8 @@
 use std::fs::{self, File};
-use std::io::{self, BufRead, Write};
+use std::io::Write;
 use clap::{App, Arg};
 use std::path::Path;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 // Define a configuration struct for your application
 struct AppConfig {
@@ -10,6 +10,53 @@ struct AppConfig {
     mode: String,
     is_folder: bool,
     n_value: Option<usize>,
+    top_k: usize,
+    reference_path: Option<String>,
+    window: usize,
+    min_freq: usize,
+    stopwords_path: Option<String>,
+    query: Option<String>,
+    is_regex: bool,
+    left_width: usize,
+    right_width: usize,
+    sort_by: String,
+    threshold: f64,
+    output_dir: Option<String>,
+    seed: u64,
+    raw_lines: bool,
+    weighting: String,
+    clusters: Option<usize>,
+    topics: usize,
+    iterations: usize,
+    vocab_size: usize,
+    arpa_path: Option<String>,
+    temperature: f64,
+    prompt: Option<String>,
+    samples: usize,
+    spectrum_max: usize,
+    stopword_langs: String,
+    exclude_stopwords: bool,
+    stem_lang: Option<String>,
+    lemmas_path: Option<String>,
+    lexicon_path: Option<String>,
+    encoding: Option<String>,
+    fix_mojibake: bool,
+}
+
+// A text file to analyse: a path, or an archive member ("<archive>/<path in the archive>") with its
+// content, which is read in a single pass over the archive when the archive is listed
+struct Input {
+    name: String,
+    bytes: Option<Vec<u8>>,
+}
+
+// Results of the generate mode for one text file
+struct FileReport {
+    file_name: String,
+    counts: Counts,
+    lang_info: Vec<LangInfo>,
+    lines: Vec<String>,
+    freq: HashMap<String, usize>,
 }
 
 mod utils {
@@ -19,39 +66,172 @@ mod utils {
     pub mod lang_detect;
     pub mod ngram;
     pub mod generators;
+    pub mod sentence;
+    pub mod diversity;
+    pub mod zipf;
+    pub mod spectrum;
+    pub mod keywords;
+    pub mod keyness;
+    pub mod collocation;
+    pub mod concordance;
+    pub mod rng;
+    pub mod near_dup;
+    pub mod duplicates;
+    pub mod similarity;
+    pub mod cluster;
+    pub mod topics;
+    pub mod bpe;
+    pub mod lm;
+    pub mod arpa;
+    pub mod markov;
+    pub mod stopwords;
+    pub mod stem;
+    pub mod coverage;
+    pub mod encoding;
+    pub mod mojibake;
+    pub mod compression;
+    pub mod archive;
 }
 
 use crate::lang_detect::LangInfo;
 use crate::counter::Counts;
-use crate::utils::{preprocessor, counter, word_freq, lang_detect, ngram, generators};
+use crate::utils::{preprocessor, counter, word_freq, lang_detect, ngram, generators, sentence, diversity, zipf, spectrum, keywords, keyness, collocation, concordance, rng, near_dup, duplicates, similarity, cluster, topics, bpe, lm, arpa, markov, stopwords, stem, coverage, encoding, mojibake, compression, archive};
+
+fn corpus_counts(reports: &[FileReport], config: &AppConfig) -> Counts {
+    // Statistics of all files taken together, as if they were one text
+    let all_lines: Vec<String> = reports.iter().flat_map(|r| r.lines.iter().cloned()).collect();
+    let mut counts = counter::counter(all_lines.clone());
+    counts.char_count = reports.iter().map(|r| r.counts.char_count).sum();
+    counts.grapheme_count = reports.iter().map(|r| r.counts.grapheme_count).sum();
+    counts.byte_count = reports.iter().map(|r| r.counts.byte_count).sum();
+    counts.sentence_count = reports.iter().map(|r| r.counts.sentence_count).sum();
+    counts.stopword_ratio = stopwords::stopword_ratio(&word_freq::word_frequency(all_lines.clone()), &stopword_list(config, &all_lines));
+    counts.mojibake_line_count = reports.iter().map(|r| r.counts.mojibake_line_count).sum();
+    counts.mojibake_rate = mojibake::mojibake_rate(counts.mojibake_line_count, counts.line_count);
+    counts
+}
 
-fn report_generator(counts: Vec<Counts>, lang_infos: Vec<Vec<LangInfo>>, file_names: Vec<String>) -> String {
+fn report_generator(reports: &[FileReport], corpus: Option<&Counts>, top_k: usize, config: &AppConfig) -> String {
     let mut report = String::new();
 
+    let mut counts: Vec<Counts> = reports.iter().map(|r| r.counts.clone()).collect();
+    let mut file_names: Vec<String> = reports.iter().map(|r| r.file_name.clone()).collect();
+    if let Some(corpus) = corpus {
+        counts.push(corpus.clone());
+        file_names.push("Corpus".to_string());
+    }
+
     // generate count table
     let count_table = generators::generate_html_table(&counts, &file_names);
     report.push_str(&count_table);
 
-    generators::generate_many_svgs(lang_infos, file_names.clone());
-    for file_name in file_names {
-        let svg_header = format!("<h3>Languages included in the {}</h3>", file_name);
+    let lang_infos: Vec<&[LangInfo]> = reports.iter().map(|r| r.lang_info.as_slice()).collect();
+    // Plots of archive members are saved next to the archive
+    let output_names: Vec<String> = reports.iter().map(|r| archive::output_name(&r.file_name)).collect();
+    generators::generate_many_svgs(&lang_infos, &output_names);
+
+    let filtered: Vec<HashMap<String, usize>> = reports
+        .iter()
+        .map(|r| stopwords::remove_stopwords(&r.freq, &excluded_words(config, &r.lines)))
+        .collect();
+    let freqs: Vec<&HashMap<String, usize>> = filtered.iter().collect();
+    let scores = keywords::tf_idf(&freqs);
+
+    for (r, score) in reports.iter().zip(&scores) {
+        let svg_header = format!("<h3>Languages included in the {}</h3>", r.file_name);
         report.push_str(&svg_header);
-        let svg_file = format!("<img src=\"{}.svg\" alt=\"SVG Image\">", file_name);
+        let svg_file = format!("<img src=\"{}.svg\" alt=\"SVG Image\">", archive::output_name(&r.file_name));
         report.push_str(&svg_file);
+        report.push_str(&power_law_section(r));
+
+        let spectrum = spectrum::frequency_spectrum(&r.freq, config.spectrum_max, spectrum::HAPAX_SAMPLES);
+        report.push_str(&format!("<h3>Frequency spectrum of the {}</h3>", r.file_name));
+        report.push_str(&generators::generate_spectrum_table(&spectrum));
+
+        report.push_str(&format!("<h3>Top keywords of the {}</h3>", r.file_name));
+        report.push_str(&generators::generate_keywords_table(&keywords::top_keywords(score, top_k)));
     }
     
     report
 }
 
-fn replace_invalid_utf8(input: &str) -> String {
-    // replace any invalid UTF-8 sequences with the Unicode replacement character (U+FFFD REPLACEMENT CHARACTER)
-    let mut encoded = Vec::new();
-    
-    for c in input.chars() {
-        encoded.extend(c.encode_utf8(&mut [0; 4]).bytes());
+fn power_law_section(report: &FileReport) -> String {
+    // Zipf rank-frequency and Heaps vocabulary growth plots for one file
+    let mut html = String::new();
+    let output_name = archive::output_name(&report.file_name);
+
+    let ranks = zipf::rank_frequency(&report.freq);
+    let zipf_fit = zipf::zipf_fit(&ranks);
+    let zipf_svg = generators::generate_loglog_svg(&ranks, &zipf_fit, "rank", "frequency");
+    generators::save_svg(&format!("{}.zipf.svg", output_name), &zipf_svg);
+
+    html.push_str(&format!("<h3>Zipf's law for the {}</h3>", report.file_name));
+    html.push_str(&format!(
+        "<p>exponent s = {:.3}, C = {:.1}, R&sup2; = {:.3}</p>",
+        -zipf_fit.exponent, zipf_fit.constant, zipf_fit.r_squared
+    ));
+    html.push_str(&format!("<img src=\"{}.zipf.svg\" alt=\"Zipf plot\">", output_name));
+
+    let curve = zipf::heaps_curve(&word_freq::tokenize(&report.lines));
+    let heaps_fit = zipf::heaps_fit(&curve);
+    let heaps_svg = generators::generate_loglog_svg(&curve, &heaps_fit, "tokens", "vocabulary size");
+    generators::save_svg(&format!("{}.heaps.svg", output_name), &heaps_svg);
+
+    html.push_str(&format!("<h3>Heaps' law for the {}</h3>", report.file_name));
+    html.push_str(&format!(
+        "<p>K = {:.3}, &beta; = {:.3}, R&sup2; = {:.3}</p>",
+        heaps_fit.constant, heaps_fit.exponent, heaps_fit.r_squared
+    ));
+    html.push_str(&format!("<img src=\"{}.heaps.svg\" alt=\"Heaps plot\">", output_name));
+
+    html
+}
+
+fn save_matrix_to_csv(file_name: &str, labels: &[String], matrix: &[Vec<f64>]) {
+    let mut file = File::create(file_name).expect("Failed to create the output file");
+
+    let header: Vec<String> = labels.iter().map(|l| generators::csv_escape(l)).collect();
+    writeln!(file, "File,{}", header.join(",")).expect("Failed to write to output file");
+
+    for (label, row) in header.iter().zip(matrix) {
+        let values: Vec<String> = row.iter().map(|v| format!("{:.6}", v)).collect();
+        writeln!(file, "{},{}", label, values.join(",")).expect("Failed to write to output file");
     }
-    
-    String::from_utf8_lossy(&encoded).to_string()
+}
+
+fn similarity_section(reports: &[FileReport], weighting: &str) -> String {
+    // Pairwise document similarity heatmaps for the folder report, also exported as CSV
+    let mut html = String::new();
+
+    let file_names: Vec<String> = reports.iter().map(|r| r.file_name.clone()).collect();
+    let freqs: Vec<&HashMap<String, usize>> = reports.iter().map(|r| &r.freq).collect();
+    let vectors = if weighting == "tf" {
+        similarity::tf_vectors(&freqs)
+    } else {
+        keywords::tf_idf(&freqs)
+    };
+
+    let cosine = similarity::cosine_matrix(&vectors);
+    let jaccard = similarity::jaccard_matrix(&freqs);
+    save_matrix_to_csv("cosine_similarity.csv", &file_names, &cosine);
+    save_matrix_to_csv("jaccard_similarity.csv", &file_names, &jaccard);
+
+    // Short labels keep the heatmap readable
+    let labels: Vec<String> = file_names
+        .iter()
+        .map(|f| Path::new(f).file_name().map_or(f.clone(), |n| n.to_string_lossy().into_owned()))
+        .collect();
+    generators::save_svg("cosine_similarity.svg", &generators::generate_heatmap_svg(&cosine, &labels));
+    generators::save_svg("jaccard_similarity.svg", &generators::generate_heatmap_svg(&jaccard, &labels));
+
+    html.push_str(&format!("<h3>Cosine similarity between files ({} vectors)</h3>", weighting.to_uppercase()));
+    html.push_str("<img src=\"cosine_similarity.svg\" alt=\"Cosine similarity heatmap\">");
+    html.push_str("<h3>Jaccard vocabulary overlap between files</h3>");
+    html.push_str("<img src=\"jaccard_similarity.svg\" alt=\"Jaccard similarity heatmap\">");
+
+    println!("Similarity matrices saved to cosine_similarity.csv and jaccard_similarity.csv");
+
+    html
 }
 
 fn save_word_frequencies_to_csv(word_frequencies: &HashMap<String, usize>) {
@@ -68,38 +248,104 @@ fn save_word_frequencies_to_csv(word_frequencies: &HashMap<String, usize>) {
     println!("Word frequencies saved to word_frequencies.csv");
 }
 
-fn process_file(file_path: &str, mode: &str, n_value: Option<usize>) -> Option<(Counts, Vec<LangInfo>)> {
-    let file = File::open(file_path).expect("Cannot open this file.");
-    let reader = io::BufReader::new(file);
-
-    let lines: Vec<String> = reader
-        .lines()
-        .filter_map(|l| {
-            match l {
-                Ok(line) => {
-                    // Replace invalid UTF-8 sequences
-                    let cleaned_line = replace_invalid_utf8(&line);
-                    Some(preprocessor::clean_text(&cleaned_line))
-                }
-                Err(_) => None, // Skip invalid lines
-            }
-        })
+fn file_bytes(file_path: &str) -> Vec<u8> {
+    // Content of a single (possibly compressed) file or archive member
+    match archive::split_member(file_path) {
+        Some((archive_path, member)) => archive::read_member(archive_path, member),
+        None => compression::read_bytes(file_path),
+    }
+    .expect("Cannot open this file.")
+}
+
+fn decode_lines(file_path: &str, bytes: &[u8], label: Option<&String>) -> Vec<String> {
+    // Lines in the detected encoding (or the given one), with a warning for undecodable bytes
+    let decoded = encoding::decode(bytes, label.map(|label| encoding::parse_encoding(label)));
+
+    if decoded.bad_lines > 0 {
+        eprintln!(
+            "Warning: {} line(s) of {} could not be fully decoded as {} ({} byte sequence(s) replaced with U+FFFD)",
+            decoded.bad_lines, file_path, decoded.encoding, decoded.bad_sequences
+        );
+    } else if label.is_none() && decoded.encoding != "UTF-8" {
+        eprintln!("Note: {} was decoded as {}", file_path, decoded.encoding);
+    }
+    decoded.lines
+}
+
+fn read_lines(input: &Input, config: &AppConfig) -> Vec<String> {
+    // Lines of a corpus file; --encoding and --fix-mojibake apply here only
+    let lines = match &input.bytes {
+        Some(bytes) => decode_lines(&input.name, bytes, config.encoding.as_ref()),
+        None => decode_lines(&input.name, &file_bytes(&input.name), config.encoding.as_ref()),
+    };
+    if config.fix_mojibake {
+        lines.iter().map(|line| mojibake::repair(line)).collect()
+    } else {
+        lines
+    }
+}
+
+fn read_aux_lines(file_path: &str) -> Vec<String> {
+    // Lines of a lexicon, stopword list or lemma table, always in its detected encoding
+    decode_lines(file_path, &file_bytes(file_path), None)
+}
+
+fn process_file(input: &Input, config: &AppConfig) -> Option<FileReport> {
+    let raw_lines = read_lines(input, config);
+
+    let lines: Vec<String> = raw_lines
+        .iter()
+        .map(|line| preprocessor::clean_text(line))
         .collect();
 
-    match mode {
+    match config.mode.as_str() {
         "generate" => {
-            let counts = counter::counter(lines.clone());
+            let mut counts = counter::counter(lines.clone());
+            (counts.char_count, counts.grapheme_count, counts.byte_count) = counter::text_size(&raw_lines);
+            counts.sentence_count = sentence::split_lines(&raw_lines).len();
             let lang_info = lang_detect::lang_detect(lines.clone());
-            Some((counts, lang_info))
+            let freq = word_freq::word_frequency(lines.clone());
+            counts.stopword_ratio = stopwords::stopword_ratio(&freq, &stopword_list(config, &lines));
+            counts.mojibake_line_count = mojibake::flagged_count(&raw_lines, &lines);
+            counts.mojibake_rate = mojibake::mojibake_rate(counts.mojibake_line_count, counts.line_count);
+            Some(FileReport {
+                file_name: input.name.clone(),
+                counts,
+                lang_info,
+                lines,
+                freq,
+            })
         }
         "frequency" => {
-            let freq = word_freq::word_frequency(lines.clone());
+            let mut freq = stopwords::remove_stopwords(&word_freq::word_frequency(lines.clone()), &excluded_words(config, &lines));
+            if let Some(normalizer) = normalizer(config) {
+                freq = normalizer.freq(&freq);
+            }
             save_word_frequencies_to_csv(&freq);
             None
         }
         "ngram" => {
-            let n_value = n_value.expect("'n' value is required for 'ngram' mode.");
-            let ngrams = ngram::generate_ngrams(lines.clone(), n_value);
+            let n_value = config.n_value.expect("'n' value is required for 'ngram' mode.");
+            // n-grams are bounded by sentences rather than by lines
+            let mut sentences: Vec<String> = sentence::split_lines(&raw_lines)
+                .iter()
+                .map(|s| preprocessor::clean_text(s))
+                .collect();
+            let mut excluded = excluded_words(config, &lines);
+            if let Some(normalizer) = normalizer(config) {
+                sentences = sentences.iter().map(|s| normalizer.line(s)).collect();
+                // The n-grams hold stems or lemmas, so the stopwords are normalized the same way
+                excluded = normalizer.set(&excluded);
+            }
+            let mut ngrams = ngram::generate_ngrams(sentences, n_value);
+            if !excluded.is_empty() {
+                // Drop every n-gram that contains a stopword
+                ngrams = ngrams
+                    .lines()
+                    .filter(|g| !g.split_whitespace().any(|w| stopwords::is_stopword(w, &excluded)))
+                    .collect::<Vec<&str>>()
+                    .join("\n");
+            }
             let file = format!("{}-gram_file.txt", n_value);
             let mut output_file = File::create(file).expect("Failed to create output file");
             write!(output_file, "{}", ngrams).expect("Failed to write to output file");
@@ -113,7 +359,9 @@ fn process_file(file_path: &str, mode: &str, n_value: Option<usize>) -> Option<(
 }
 
 fn is_text_file(file_path: &Path) -> bool {
-    if let Some(extension) = file_path.extension() {
+    // A compressed file counts by the extension under the compression one, e.g. "corpus.txt.gz"
+    let path = file_path.to_string_lossy();
+    if let Some(extension) = Path::new(compression::strip_extension(&path)).extension() {
         if let Some(ext_str) = extension.to_str() {
             // Add more text file extensions as needed
             return ext_str == "txt" || ext_str == "text" || ext_str == "md";
@@ -122,30 +370,740 @@ fn is_text_file(file_path: &Path) -> bool {
     false
 }
 
-fn process_folder(folder_path: &str, mode: &str, n_value: Option<usize>) -> (Vec<Counts>, Vec<Vec<LangInfo>>, Vec<String>) {
-    let mut counters = Vec::new();
-    let mut lang_infos = Vec::new();
-    let mut file_names = Vec::new();
+fn text_files(folder_path: &str) -> Vec<Input> {
+    // Text files directly under the folder, or text members of a zip or tar archive, in name order;
+    // archive members are read here, in one pass over the archive
+    let mut files = Vec::new();
+
+    if archive::is_archive(folder_path) {
+        let members = archive::members(folder_path, |member| is_text_file(Path::new(member))).expect("Cannot read this archive.");
+        files.extend(members.into_iter().map(|(member, bytes)| Input {
+            name: format!("{}/{}", folder_path, member),
+            bytes: Some(bytes),
+        }));
+    }
 
     if let Ok(entries) = fs::read_dir(folder_path) {
-        for entry in entries {
-            if let Ok(entry) = entry {
-                let path = entry.path();
-                if path.is_file() && is_text_file(&path) {
-                    if let Some((counts, lang_info)) = process_file(&path.to_string_lossy(), mode, n_value) {
-                        counters.push(counts);
-                        lang_infos.push(lang_info);
-                        file_names.push(path.to_string_lossy().into_owned());
-                    }
-                }
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_file() && is_text_file(&path) {
+                files.push(Input { name: path.to_string_lossy().into_owned(), bytes: None });
+            }
+        }
+    }
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+    files
+}
+
+fn input_files(config: &AppConfig) -> Vec<Input> {
+    if config.is_folder {
+        text_files(&config.input_path)
+    } else {
+        vec![Input { name: config.input_path.clone(), bytes: None }]
+    }
+}
+
+fn file_names(inputs: &[Input]) -> Vec<String> {
+    inputs.iter().map(|input| input.name.clone()).collect()
+}
+
+fn process_folder(folder_path: &str, config: &AppConfig) -> Vec<FileReport> {
+    text_files(folder_path)
+        .iter()
+        .filter_map(|input| process_file(input, config))
+        .collect()
+}
+
+fn sentences_mode(config: &AppConfig) {
+    // Sentences of every input file, in file order, to one output file
+    let mut output_file = File::create("sentences.txt").expect("Failed to create output file");
+    let mut count = 0;
+    for file in input_files(config) {
+        for s in sentence::split_lines(&read_lines(&file, config)) {
+            writeln!(output_file, "{}", s).expect("Failed to write to output file");
+            count += 1;
+        }
+    }
+    println!("{} sentences saved to sentences.txt", count);
+}
+
+fn keywords_mode(config: &AppConfig) {
+    // TF-IDF over all input files, top-K terms per file to CSV and JSON
+    let inputs = input_files(config);
+    let file_names = file_names(&inputs);
+    let normalizer = normalizer(config);
+    let freqs: Vec<HashMap<String, usize>> = inputs
+        .iter()
+        .map(|file| {
+            let lines: Vec<String> = read_lines(file, config).iter().map(|l| preprocessor::clean_text(l)).collect();
+            let freq = stopwords::remove_stopwords(&word_freq::word_frequency(lines.clone()), &excluded_words(config, &lines));
+            match &normalizer {
+                Some(n) => n.freq(&freq),
+                None => freq,
+            }
+        })
+        .collect();
+
+    let freq_refs: Vec<&HashMap<String, usize>> = freqs.iter().collect();
+    let top: Vec<Vec<(String, f64)>> = keywords::tf_idf(&freq_refs)
+        .iter()
+        .map(|scores| keywords::top_keywords(scores, config.top_k))
+        .collect();
+
+    let mut csv = File::create("keywords.csv").expect("Failed to create the output file");
+    writeln!(csv, "File,Rank,Keyword,Score").expect("Failed to write to output file");
+    for (file_name, words) in file_names.iter().zip(&top) {
+        for (rank, (word, score)) in words.iter().enumerate() {
+            writeln!(csv, "{},{},{},{:.6}", generators::csv_escape(file_name), rank + 1, word, score)
+                .expect("Failed to write to output file");
+        }
+    }
+
+    let mut json = File::create("keywords.json").expect("Failed to create the output file");
+    json.write_all(generators::generate_keywords_json(&file_names, &top).as_bytes())
+        .expect("Failed to write to output file");
+
+    println!("Keywords saved to keywords.csv and keywords.json");
+}
+
+fn corpus_lines(path: &str, config: &AppConfig) -> Vec<String> {
+    // Raw lines of a file, or of every text file in a folder or archive
+    if Path::new(path).is_dir() || archive::is_archive(path) {
+        text_files(path).iter().flat_map(|file| read_lines(file, config)).collect()
+    } else {
+        read_lines(&Input { name: path.to_string(), bytes: None }, config)
+    }
+}
+
+fn save_keyness_to_csv(file_name: &str, scores: &[keyness::Keyness]) {
+    let mut file = File::create(file_name).expect("Failed to create the output file");
+    writeln!(file, "Word,Target,Reference,LogLikelihood,ChiSquare,PercentDiff,LogRatio")
+        .expect("Failed to write to output file");
+
+    for k in scores {
+        writeln!(
+            file,
+            "{},{},{},{:.4},{:.4},{:.2},{:.4}",
+            k.word, k.target_freq, k.reference_freq, k.log_likelihood, k.chi_square, k.percent_diff, k.log_ratio
+        )
+        .expect("Failed to write to output file");
+    }
+}
+
+fn compare_mode(config: &AppConfig) {
+    // Keyness of the target corpus (input_path) against the reference corpus
+    let reference_path = config
+        .reference_path
+        .as_ref()
+        .expect("A reference file or folder is required for 'compare' mode.");
+
+    let mut counts = Vec::new();
+    let mut freqs = Vec::new();
+    for path in [&config.input_path, reference_path] {
+        let raw_lines = corpus_lines(path, config);
+        let lines: Vec<String> = raw_lines.iter().map(|l| preprocessor::clean_text(l)).collect();
+        let mut c = counter::counter(lines.clone());
+        (c.char_count, c.grapheme_count, c.byte_count) = counter::text_size(&raw_lines);
+        c.sentence_count = sentence::split_lines(&raw_lines).len();
+        let freq = word_freq::word_frequency(lines.clone());
+        c.stopword_ratio = stopwords::stopword_ratio(&freq, &stopword_list(config, &lines));
+        counts.push(c);
+        freqs.push(stopwords::remove_stopwords(&freq, &excluded_words(config, &lines)));
+    }
+
+    let (positive, negative) = keyness::split_by_direction(keyness::keyness(&freqs[0], &freqs[1]));
+    save_keyness_to_csv("keyness_positive.csv", &positive);
+    save_keyness_to_csv("keyness_negative.csv", &negative);
+    println!("Keywords saved to keyness_positive.csv and keyness_negative.csv");
+
+    // Side-by-side report page
+    let names = vec![
+        format!("Target: {}", config.input_path),
+        format!("Reference: {}", reference_path),
+    ];
+    let mut report = generators::generate_html_table(&counts, &names);
+    report.push_str("<div style=\"display:flex; gap:40px\"><div>");
+    report.push_str(&format!("<h3>Positive keywords (overused in {})</h3>", config.input_path));
+    report.push_str(&generators::generate_keyness_table(&positive[..positive.len().min(config.top_k)]));
+    report.push_str("</div><div>");
+    report.push_str(&format!("<h3>Negative keywords (underused in {})</h3>", config.input_path));
+    report.push_str(&generators::generate_keyness_table(&negative[..negative.len().min(config.top_k)]));
+    report.push_str("</div></div>");
+
+    let mut output_file = File::create("report_compare.html").expect("Failed to create output file");
+    output_file.write_all(report.as_bytes()).expect("Failed to write report to file");
+    println!("Report saved to report_compare.html");
+}
+
+fn load_word_list(path: &str) -> HashSet<String> {
+    // One word per line, lowercased; blank lines and lines starting with '#' are ignored
+    read_aux_lines(path)
+        .iter()
+        .map(|line| line.trim().to_lowercase())
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .collect()
+}
+
+fn user_stopwords(config: &AppConfig) -> HashSet<String> {
+    // Words of the --stopwords file, if any
+    match &config.stopwords_path {
+        Some(path) => load_word_list(path),
+        None => HashSet::new(),
+    }
+}
+
+fn stopword_list(config: &AppConfig, lines: &[String]) -> HashSet<String> {
+    // Bundled lists of the chosen languages (by default those detected in the lines) plus the user's list
+    let langs = if config.stopword_langs.eq_ignore_ascii_case("auto") {
+        stopwords::detected_langs(&lang_detect::lang_detect(lines.to_vec()), &word_freq::word_frequency(lines.to_vec()))
+    } else {
+        stopwords::parse_langs(&config.stopword_langs)
+    };
+    let mut list = stopwords::stopword_set(&langs);
+    list.extend(user_stopwords(config));
+    list
+}
+
+fn excluded_words(config: &AppConfig, lines: &[String]) -> HashSet<String> {
+    // Words to leave out of frequency, n-gram, keyword and collocation outputs:
+    // the user's list always, the bundled lists only with --exclude-stopwords
+    if config.exclude_stopwords {
+        stopword_list(config, lines)
+    } else {
+        user_stopwords(config)
+    }
+}
+
+fn normalizer(config: &AppConfig) -> Option<stem::Normalizer> {
+    // Stemmer and/or lemma table, when either was asked for
+    if config.stem_lang.is_none() && config.lemmas_path.is_none() {
+        return None;
+    }
+    let stemmer = config.stem_lang.as_ref().map(|name| stem::parse_lang(name));
+    let lemmas = match &config.lemmas_path {
+        Some(path) => stem::load_lemmas(&read_aux_lines(path)),
+        None => HashMap::new(),
+    };
+    Some(stem::Normalizer::new(stemmer, lemmas))
+}
+
+fn collocations_mode(config: &AppConfig) {
+    // Association measures for word pairs inside sentences, one ranked CSV per measure
+    let sentences: Vec<String> = input_files(config)
+        .iter()
+        .flat_map(|file| sentence::split_lines(&read_lines(file, config)))
+        .map(|s| preprocessor::clean_text(&s))
+        .collect();
+    let units: Vec<Vec<String>> = sentences.iter().map(|s| word_freq::tokenize(std::slice::from_ref(s))).collect();
+    let excluded = excluded_words(config, &sentences);
+
+    let mut colls = collocation::collocations(&units, config.window, config.min_freq, &excluded);
+
+    for measure in collocation::MEASURES {
+        collocation::rank_by(&mut colls, measure);
+
+        let file_name = format!("collocations_{}.csv", measure);
+        let mut file = File::create(&file_name).expect("Failed to create the output file");
+        writeln!(file, "First,Second,Frequency,PMI,TScore,Dice,LogLikelihood,MI3").expect("Failed to write to output file");
+        for c in &colls {
+            writeln!(
+                file,
+                "{},{},{},{:.4},{:.4},{:.4},{:.4},{:.4}",
+                c.first, c.second, c.freq, c.pmi, c.t_score, c.dice, c.log_likelihood, c.mi3
+            )
+            .expect("Failed to write to output file");
+        }
+        println!("Collocations ranked by {} saved to {}", measure, file_name);
+    }
+}
+
+fn concordance_mode(config: &AppConfig) {
+    // Keyword-in-context lines over the raw text of every input file
+    let query = config.query.as_ref().expect("A --query word or regex is required for 'concordance' mode.");
+    let pattern = concordance::query_regex(query, config.is_regex);
+
+    let mut kwic: Vec<concordance::KwicLine> = input_files(config)
+        .iter()
+        .flat_map(|file| concordance::concordance(&read_lines(file, config), &file.name, &pattern, config.left_width, config.right_width))
+        .collect();
+    concordance::sort_lines(&mut kwic, &config.sort_by);
+
+    for k in &kwic {
+        println!("{}", concordance::format_line(k, config.left_width));
+    }
+
+    let html = generators::generate_concordance_html(&kwic, query);
+    let mut output_file = File::create("concordance.html").expect("Failed to create output file");
+    output_file.write_all(html.as_bytes()).expect("Failed to write report to file");
+    println!("{} concordance lines saved to concordance.html", kwic.len());
+}
+
+fn write_deduplicated_corpus(output_dir: &str, files: &[String], raw: &[Vec<String>], keep_file: &[bool], keep_line: &[Vec<bool>]) {
+    // Copy of the kept files, without the dropped lines, under output_dir with the same file names
+    fs::create_dir_all(output_dir).expect("Failed to create the output folder");
+
+    for (i, file) in files.iter().enumerate() {
+        if !keep_file[i] {
+            continue;
+        }
+        // Archive members keep their path inside the archive; the text is written uncompressed,
+        // so a compression extension is dropped ("a.txt.gz" -> "a.txt")
+        let name = match archive::split_member(file) {
+            Some((_, member)) => member,
+            None => Path::new(file).file_name().and_then(|n| n.to_str()).expect("Input file has no name"),
+        };
+        let output_path = Path::new(output_dir).join(compression::strip_extension(name));
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent).expect("Failed to create the output folder");
+        }
+        let mut output_file = File::create(output_path).expect("Failed to create output file");
+        for (line, keep) in raw[i].iter().zip(&keep_line[i]) {
+            if *keep {
+                writeln!(output_file, "{}", line).expect("Failed to write to output file");
             }
         }
     }
 
-    (counters, lang_infos, file_names)
+    println!("Deduplicated corpus saved to {}", output_dir);
+}
+
+fn dedup_mode(config: &AppConfig) {
+    // Near-duplicate files and lines from MinHash signatures with LSH banding
+    let inputs = input_files(config);
+    let files = file_names(&inputs);
+    let raw: Vec<Vec<String>> = inputs.iter().map(|file| read_lines(file, config)).collect();
+    let cleaned: Vec<Vec<String>> = raw
+        .iter()
+        .map(|lines| lines.iter().map(|l| preprocessor::clean_text(l)).collect())
+        .collect();
+
+    let hasher = near_dup::MinHasher::new(config.seed);
+
+    // Whole files
+    let file_signatures: Vec<Vec<u64>> = cleaned.iter().map(|lines| hasher.signature(&lines.join(" "))).collect();
+    let file_clusters = near_dup::near_duplicate_clusters(&file_signatures, config.threshold);
+
+    let mut csv = File::create("near_duplicate_files.csv").expect("Failed to create the output file");
+    writeln!(csv, "Cluster,File").expect("Failed to write to output file");
+    let mut keep_file = vec![true; files.len()];
+    for (c, cluster) in file_clusters.iter().enumerate() {
+        for (k, &i) in cluster.iter().enumerate() {
+            writeln!(csv, "{},{}", c + 1, generators::csv_escape(&files[i])).expect("Failed to write to output file");
+            // The first file of every cluster is the one that is kept
+            keep_file[i] = k == 0;
+        }
+    }
+
+    // Lines across the files that are kept
+    let line_signatures: Vec<Vec<Vec<u64>>> = cleaned
+        .iter()
+        .zip(&keep_file)
+        .map(|(lines, &keep)| if keep { lines.iter().map(|l| hasher.signature(l)).collect() } else { Vec::new() })
+        .collect();
+    let line_clusters = near_dup::line_clusters(&line_signatures, &keep_file, config.threshold);
+
+    let mut csv = File::create("near_duplicate_lines.csv").expect("Failed to create the output file");
+    writeln!(csv, "Cluster,File,Line,Text").expect("Failed to write to output file");
+    let mut keep_line: Vec<Vec<bool>> = raw.iter().map(|lines| vec![true; lines.len()]).collect();
+    for (c, cluster) in line_clusters.iter().enumerate() {
+        for (k, &(f, l)) in cluster.iter().enumerate() {
+            writeln!(csv, "{},{},{},{}", c + 1, generators::csv_escape(&files[f]), l + 1, generators::csv_escape(&raw[f][l]))
+                .expect("Failed to write to output file");
+            keep_line[f][l] = k == 0;
+        }
+    }
+
+    let dropped_files = keep_file.iter().filter(|k| !**k).count();
+    let dropped_lines = keep_line.iter().flatten().filter(|k| !**k).count();
+    println!(
+        "{} near-duplicate file clusters ({} redundant files) saved to near_duplicate_files.csv",
+        file_clusters.len(), dropped_files
+    );
+    println!(
+        "{} near-duplicate line clusters ({} redundant lines) saved to near_duplicate_lines.csv",
+        line_clusters.len(), dropped_lines
+    );
+
+    if let Some(output_dir) = &config.output_dir {
+        write_deduplicated_corpus(output_dir, &files, &raw, &keep_file, &keep_line);
+    }
+}
+
+fn duplicates_mode(config: &AppConfig) {
+    // Exact duplicate lines per file and across all input files
+    let inputs = input_files(config);
+    let files = file_names(&inputs);
+    let raw: Vec<Vec<String>> = inputs.iter().map(|file| read_lines(file, config)).collect();
+    let keys: Vec<Vec<Option<u64>>> = raw
+        .iter()
+        .map(|lines| lines.iter().map(|l| duplicates::line_key(l, config.raw_lines)).collect())
+        .collect();
+
+    let mut csv = File::create("duplicate_rate.csv").expect("Failed to create the output file");
+    writeln!(csv, "File,Lines,DuplicateLines,DuplicateRate").expect("Failed to write to output file");
+    for (file, file_keys) in files.iter().zip(&keys) {
+        let stats = duplicates::duplicate_stats(file_keys);
+        println!("{}: {} of {} lines are duplicates ({:.2}%)", file, stats.duplicate_lines, stats.lines, stats.rate);
+        writeln!(csv, "{},{},{},{:.4}", generators::csv_escape(file), stats.lines, stats.duplicate_lines, stats.rate)
+            .expect("Failed to write to output file");
+    }
+
+    // Across the folder a line counts as a duplicate if it appeared anywhere before it
+    let all_keys: Vec<Option<u64>> = keys.iter().flatten().copied().collect();
+    let corpus = duplicates::duplicate_stats(&all_keys);
+    println!("Corpus: {} of {} lines are duplicates ({:.2}%)", corpus.duplicate_lines, corpus.lines, corpus.rate);
+    writeln!(csv, "Corpus,{},{},{:.4}", corpus.lines, corpus.duplicate_lines, corpus.rate)
+        .expect("Failed to write to output file");
+    println!("Duplicate rates saved to duplicate_rate.csv");
+
+    let all_lines: Vec<&String> = raw.iter().flatten().collect();
+    let mut csv = File::create("duplicate_lines.csv").expect("Failed to create the output file");
+    writeln!(csv, "Count,Line").expect("Failed to write to output file");
+    for (line, count) in duplicates::most_repeated(&all_lines, &all_keys, config.top_k) {
+        writeln!(csv, "{},{}", count, generators::csv_escape(&line)).expect("Failed to write to output file");
+    }
+    println!("Most repeated lines saved to duplicate_lines.csv");
+
+    if let Some(output_dir) = &config.output_dir {
+        // Keep the first occurrence of every line in the whole corpus, in the original order
+        let mut seen = HashSet::new();
+        let keep_line: Vec<Vec<bool>> = keys
+            .iter()
+            .map(|file_keys| duplicates::keep_first_occurrences(file_keys, &mut seen))
+            .collect();
+        write_deduplicated_corpus(output_dir, &files, &raw, &vec![true; files.len()], &keep_line);
+    }
+}
+
+fn cluster_mode(config: &AppConfig) {
+    // k-means over TF-IDF vectors of the input files; k is picked by silhouette score unless given
+    let inputs = input_files(config);
+    let files = file_names(&inputs);
+    let freqs: Vec<HashMap<String, usize>> = inputs
+        .iter()
+        .map(|file| {
+            let lines: Vec<String> = read_lines(file, config).iter().map(|l| preprocessor::clean_text(l)).collect();
+            word_freq::word_frequency(lines)
+        })
+        .collect();
+
+    let freq_refs: Vec<&HashMap<String, usize>> = freqs.iter().collect();
+    let vectors: Vec<HashMap<String, f64>> = keywords::tf_idf(&freq_refs).iter().map(cluster::normalize).collect();
+
+    let clustering = match config.clusters {
+        Some(k) => cluster::kmeans(&vectors, k, config.seed),
+        None => cluster::choose_k(&vectors, cluster::MAX_AUTO_K, config.seed),
+    };
+    println!("{} clusters, silhouette score {:.4}", clustering.k, clustering.silhouette);
+
+    let mut csv = File::create("clusters.csv").expect("Failed to create the output file");
+    writeln!(csv, "File,Cluster").expect("Failed to write to output file");
+    for (file, c) in files.iter().zip(&clustering.assignments) {
+        writeln!(csv, "{},{}", generators::csv_escape(file), c + 1).expect("Failed to write to output file");
+    }
+
+    let mut members: Vec<Vec<String>> = vec![Vec::new(); clustering.k];
+    for (file, &c) in files.iter().zip(&clustering.assignments) {
+        members[c].push(file.clone());
+    }
+    let terms: Vec<Vec<String>> = clustering.centroids.iter().map(|c| cluster::top_terms(c, config.top_k)).collect();
+
+    let mut csv = File::create("cluster_terms.csv").expect("Failed to create the output file");
+    writeln!(csv, "Cluster,Size,TopTerms").expect("Failed to write to output file");
+    for (c, (files, words)) in members.iter().zip(&terms).enumerate() {
+        writeln!(csv, "{},{},{}", c + 1, files.len(), words.join(" ")).expect("Failed to write to output file");
+    }
+    println!("Cluster assignments saved to clusters.csv and top terms to cluster_terms.csv");
+
+    let html = generators::generate_cluster_html(&clustering, &members, &terms);
+    let mut output_file = File::create("report_clusters.html").expect("Failed to create output file");
+    output_file.write_all(html.as_bytes()).expect("Failed to write report to file");
+    println!("Report saved to report_clusters.html");
+}
+
+fn topics_mode(config: &AppConfig) {
+    // LDA topic model over the token streams of the input files
+    let inputs = input_files(config);
+    let files = file_names(&inputs);
+    let docs: Vec<Vec<String>> = inputs
+        .iter()
+        .map(|file| {
+            let lines: Vec<String> = read_lines(file, config).iter().map(|l| preprocessor::clean_text(l)).collect();
+            word_freq::tokenize(&lines)
+        })
+        .collect();
+
+    let model = topics::lda(&docs, config.topics, config.iterations, config.seed);
+    let top: Vec<Vec<(String, f64)>> = (0..model.k).map(|t| model.top_words(t, config.top_k)).collect();
+    let coherence: Vec<f64> = top
+        .iter()
+        .map(|words| {
+            let words: Vec<String> = words.iter().map(|(w, _)| w.clone()).collect();
+            topics::coherence(&words, &docs)
+        })
+        .collect();
+    let mixtures: Vec<Vec<f64>> = (0..docs.len()).map(|d| model.doc_mixture(d)).collect();
+
+    let mut csv = File::create("topics.csv").expect("Failed to create the output file");
+    writeln!(csv, "Topic,Rank,Word,Probability").expect("Failed to write to output file");
+    for (t, words) in top.iter().enumerate() {
+        for (rank, (word, p)) in words.iter().enumerate() {
+            writeln!(csv, "{},{},{},{:.6}", t + 1, rank + 1, word, p).expect("Failed to write to output file");
+        }
+    }
+
+    let mut csv = File::create("doc_topics.csv").expect("Failed to create the output file");
+    let header: Vec<String> = (1..=model.k).map(|t| format!("Topic{}", t)).collect();
+    writeln!(csv, "File,{}", header.join(",")).expect("Failed to write to output file");
+    for (file, mixture) in files.iter().zip(&mixtures) {
+        let values: Vec<String> = mixture.iter().map(|p| format!("{:.6}", p)).collect();
+        writeln!(csv, "{},{}", generators::csv_escape(file), values.join(",")).expect("Failed to write to output file");
+    }
+
+    let mut json = File::create("topics.json").expect("Failed to create the output file");
+    json.write_all(generators::generate_topics_json(&top, &coherence, &files, &mixtures).as_bytes())
+        .expect("Failed to write to output file");
+    println!("{} topics saved to topics.csv, doc_topics.csv and topics.json", model.k);
+
+    let html = generators::generate_topics_html(&top, &coherence, &files, &mixtures);
+    let mut output_file = File::create("report_topics.html").expect("Failed to create output file");
+    output_file.write_all(html.as_bytes()).expect("Failed to write report to file");
+    println!("Report saved to report_topics.html");
+}
+
+fn bpe_mode(config: &AppConfig) {
+    // Learn BPE merges from the corpus word frequencies and save them in the vocab.json / merges.txt format
+    let lines: Vec<String> = input_files(config)
+        .iter()
+        .flat_map(|file| read_lines(file, config))
+        .map(|l| preprocessor::clean_text(&l))
+        .collect();
+    let freq = word_freq::word_frequency(lines);
+    let model = bpe::train(&freq, config.vocab_size);
+
+    let mut json = File::create("vocab.json").expect("Failed to create the output file");
+    json.write_all(generators::generate_vocab_json(&model.vocab).as_bytes())
+        .expect("Failed to write to output file");
+
+    let mut merges = File::create("merges.txt").expect("Failed to create the output file");
+    writeln!(merges, "#version: 0.2").expect("Failed to write to output file");
+    for (left, right) in &model.merges {
+        writeln!(merges, "{} {}", left, right).expect("Failed to write to output file");
+    }
+    println!(
+        "{} merges, vocabulary of {} tokens saved to vocab.json and merges.txt",
+        model.merges.len(),
+        model.vocab.len()
+    );
+
+    // Average tokens per word (fertility) for every language, weighted by word frequency
+    let mut per_lang: HashMap<String, (usize, usize)> = HashMap::new();
+    for (word, &count) in &freq {
+        let entry = per_lang.entry(lang_detect::word_lang(word)).or_insert((0, 0));
+        entry.0 += count;
+        entry.1 += count * model.encode_word(word).len();
+    }
+    let mut per_lang: Vec<(String, (usize, usize))> = per_lang.into_iter().collect();
+    per_lang.sort_by(|a, b| b.1 .0.cmp(&a.1 .0).then_with(|| a.0.cmp(&b.0)));
+
+    let mut csv = File::create("bpe_tokens_per_word.csv").expect("Failed to create the output file");
+    writeln!(csv, "Language,Words,Tokens,TokensPerWord").expect("Failed to write to output file");
+    for (lang, (words, tokens)) in &per_lang {
+        let ratio = *tokens as f64 / *words as f64;
+        writeln!(csv, "{},{},{},{:.4}", lang, words, tokens, ratio).expect("Failed to write to output file");
+        println!("{}: {:.4} tokens per word ({} words)", lang, ratio, words);
+    }
+    println!("Tokens per word by language saved to bpe_tokens_per_word.csv");
+}
+
+fn lm_mode(config: &AppConfig) {
+    // Train an interpolated modified Kneser-Ney model on input_path (or load an .arpa model)
+    // and score the held-out file line by line
+    let held_out = config
+        .reference_path
+        .as_ref()
+        .expect("A held-out file is required for 'lm' mode.");
+
+    if compression::strip_extension(&config.input_path).ends_with(".arpa") {
+        let bytes = compression::read_bytes(&config.input_path).expect("Failed to read the ARPA file");
+        let model = arpa::read_arpa(&String::from_utf8_lossy(&bytes));
+        return save_perplexity(&model, held_out, config);
+    }
+
+    let order = config.n_value.unwrap_or(3);
+    let training: Vec<String> = corpus_lines(&config.input_path, config).iter().map(|l| preprocessor::clean_text(l)).collect();
+    let model = lm::KneserNey::train(&training, order);
+
+    if let Some(path) = &config.arpa_path {
+        let mut file = File::create(path).expect("Failed to create the output file");
+        file.write_all(arpa::write_arpa(&model).as_bytes()).expect("Failed to write to output file");
+        println!("Model saved to {}", path);
+    }
+    save_perplexity(&model, held_out, config);
+}
+
+fn save_perplexity(model: &dyn lm::LanguageModel, held_out: &str, config: &AppConfig) {
+    // Per-line and overall perplexity of the held-out text; the most perplexing lines are printed
+    let raw_lines = corpus_lines(held_out, config);
+    let mut scored: Vec<(usize, &String, lm::LineScore)> = Vec::new();
+    for (i, line) in raw_lines.iter().enumerate() {
+        let cleaned = preprocessor::clean_text(line);
+        if !cleaned.trim().is_empty() {
+            scored.push((i + 1, line, lm::score_line(model, &cleaned)));
+        }
+    }
+
+    let mut csv = File::create("lm_perplexity.csv").expect("Failed to create the output file");
+    writeln!(csv, "Line,Tokens,OOV,Log10Prob,Perplexity,Text").expect("Failed to write to output file");
+    for (number, line, score) in &scored {
+        writeln!(
+            csv,
+            "{},{},{},{:.4},{:.4},{}",
+            number, score.tokens, score.oov, score.log10_prob, score.perplexity, generators::csv_escape(line.trim())
+        )
+        .expect("Failed to write to output file");
+    }
+
+    let scores: Vec<lm::LineScore> = scored.iter().map(|(_, _, s)| s.clone()).collect();
+    println!(
+        "Order {} model: perplexity {:.4} over {} lines, {} OOV tokens",
+        model.order(),
+        lm::perplexity(&scores),
+        scores.len(),
+        scores.iter().map(|s| s.oov).sum::<usize>()
+    );
+    println!("Per-line perplexity saved to lm_perplexity.csv");
+
+    scored.sort_by(|a, b| b.2.perplexity.total_cmp(&a.2.perplexity).then_with(|| a.0.cmp(&b.0)));
+    for (number, line, score) in scored.iter().take(config.top_k) {
+        println!("{:>10.2}  line {}: {}", score.perplexity, number, line.trim());
+    }
+}
+
+fn generate_text_mode(config: &AppConfig) {
+    // Sample sentences from the n-gram counts of the corpus sentences
+    let sentences: Vec<Vec<String>> = sentence::split_lines(&corpus_lines(&config.input_path, config))
+        .iter()
+        .map(|s| word_freq::tokenize(&[preprocessor::clean_text(s)]))
+        .collect();
+    let chain = markov::MarkovChain::train(&sentences, config.n_value.unwrap_or(3));
+
+    let prompt = config
+        .prompt
+        .as_ref()
+        .map(|p| word_freq::tokenize(&[preprocessor::clean_text(p)]))
+        .unwrap_or_default();
+    let mut rng = rng::Rng::new(config.seed);
+
+    let mut file = File::create("generated_text.txt").expect("Failed to create the output file");
+    for _ in 0..config.samples {
+        let text = chain.generate(&prompt, config.temperature, &mut rng).join(" ");
+        println!("{}", text);
+        writeln!(file, "{}", text).expect("Failed to write to output file");
+    }
+    println!("Generated text saved to generated_text.txt");
+}
+
+fn coverage_mode(config: &AppConfig) {
+    // Lexicon coverage and OOV words of every input file and of the whole corpus
+    let lexicon_path = config
+        .lexicon_path
+        .as_ref()
+        .expect("A word list is required for 'coverage' mode (--lexicon).");
+    let lexicon = load_word_list(lexicon_path);
+
+    let inputs = input_files(config);
+    let mut names = file_names(&inputs);
+    let mut freqs: Vec<HashMap<String, usize>> = inputs
+        .iter()
+        .map(|file| {
+            let lines: Vec<String> = read_lines(file, config).iter().map(|l| preprocessor::clean_text(l)).collect();
+            word_freq::word_frequency(lines)
+        })
+        .collect();
+    let mut corpus: HashMap<String, usize> = HashMap::new();
+    for freq in &freqs {
+        for (word, count) in freq {
+            *corpus.entry(word.clone()).or_insert(0) += count;
+        }
+    }
+    names.push("Corpus".to_string());
+    freqs.push(corpus);
+    let results: Vec<coverage::Coverage> = freqs.iter().map(|freq| coverage::coverage(freq, &lexicon)).collect();
+
+    let mut csv = File::create("coverage.csv").expect("Failed to create the output file");
+    writeln!(csv, "File,Tokens,Types,TokenCoverage,TypeCoverage,OOVRate").expect("Failed to write to output file");
+    for (name, c) in names.iter().zip(&results) {
+        writeln!(
+            csv,
+            "{},{},{},{:.2},{:.2},{:.2}",
+            generators::csv_escape(name), c.tokens, c.types, c.token_coverage(), c.type_coverage(), c.oov_rate()
+        )
+        .expect("Failed to write to output file");
+    }
+
+    let mut csv = File::create("oov_words.csv").expect("Failed to create the output file");
+    writeln!(csv, "File,Rank,Word,Count").expect("Failed to write to output file");
+    for (name, c) in names.iter().zip(&results) {
+        for (rank, (word, count)) in c.oov.iter().take(config.top_k).enumerate() {
+            writeln!(csv, "{},{},{},{}", generators::csv_escape(name), rank + 1, word, count)
+                .expect("Failed to write to output file");
+        }
+    }
+
+    let corpus = &results[results.len() - 1];
+    println!(
+        "Token coverage {:.2}%, type coverage {:.2}%, OOV rate {:.2}% ({} files)",
+        corpus.token_coverage(), corpus.type_coverage(), corpus.oov_rate(), names.len() - 1
+    );
+    for (word, count) in corpus.oov.iter().take(config.top_k) {
+        println!("{:>8}  {}", count, word);
+    }
+    println!("Coverage saved to coverage.csv and OOV words to oov_words.csv");
+}
+
+fn mojibake_mode(config: &AppConfig) {
+    // Mojibake rate of every input file, and the flagged lines with their score and repair
+    let files = input_files(config);
+    let mut rates = File::create("mojibake.csv").expect("Failed to create the output file");
+    writeln!(rates, "File,Lines,MojibakeLines,MojibakeRate").expect("Failed to write to output file");
+    let mut flagged = File::create("mojibake_lines.csv").expect("Failed to create the output file");
+    writeln!(flagged, "File,Line,Score,Text,Repaired").expect("Failed to write to output file");
+
+    let (mut total_lines, mut total_flagged) = (0, 0);
+    for file in &files {
+        // Lines are counted as in the reports: the ones that are not empty after cleaning
+        let raw_lines = read_lines(file, config);
+        let lines: Vec<String> = raw_lines.iter().map(|line| preprocessor::clean_text(line)).collect();
+        let line_count = lines.iter().filter(|line| !line.is_empty()).count();
+        let mut count = 0;
+        for (i, (raw, line)) in raw_lines.iter().zip(&lines).enumerate() {
+            if mojibake::is_flagged(raw, line) {
+                count += 1;
+                writeln!(
+                    flagged,
+                    "{},{},{:.2},{},{}",
+                    generators::csv_escape(&file.name), i + 1, mojibake::score(raw), generators::csv_escape(raw), generators::csv_escape(&mojibake::repair(raw))
+                )
+                .expect("Failed to write to output file");
+            }
+        }
+        writeln!(rates, "{},{},{},{:.2}", generators::csv_escape(&file.name), line_count, count, mojibake::mojibake_rate(count, line_count))
+            .expect("Failed to write to output file");
+        total_lines += line_count;
+        total_flagged += count;
+    }
+
+    println!("{} of {} lines look like mojibake", total_flagged, total_lines);
+    println!("Mojibake rates saved to mojibake.csv and flagged lines to mojibake_lines.csv");
 }
 
 fn configure_app() -> AppConfig {
+    let stopword_names = stopwords::names();
+    let stopword_values: Vec<&str> = stopword_names.iter().map(|name| name.as_str()).collect();
+    let stem_names = stem::names();
+    let stem_values: Vec<&str> = stem_names.iter().map(|name| name.as_str()).collect();
     let matches = App::new("CorpuX: Text corpus analysis tool")
         .version("1.0")
         .author("Thura Aung <66011606@kmitl.ac.th>")
@@ -156,12 +1114,18 @@ fn configure_app() -> AppConfig {
                 .required(true)
                 .index(1),
         )
+        .arg(
+            Arg::with_name("reference_path")
+                .help("The reference file or folder for 'compare' mode, or the held-out file for 'lm' mode")
+                .required(false)
+                .index(2),
+        )
         .arg(
             Arg::with_name("mode")
                 .help("Select the analysis mode")
                 .short("m")
                 .long("mode")
-                .possible_values(&["generate", "frequency", "ngram"])
+                .possible_values(&["generate", "frequency", "ngram", "sentences", "keywords", "compare", "collocations", "concordance", "dedup", "duplicates", "cluster", "topics", "bpe", "lm", "generate-text", "coverage", "mojibake"])
                 .required(true)
                 .takes_value(true),
         )
@@ -175,57 +1139,401 @@ fn configure_app() -> AppConfig {
         )
         .arg(
             Arg::with_name("n_value")
-                .help("Value of 'n' for n-gram analysis")
+                .help("Value of 'n' for n-gram analysis, or the model order for 'lm' and 'generate-text'")
                 .short("n")
                 .long("nvalue")
                 .required(false)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("top_k")
+                .help("Number of top entries (keywords, repeated lines) to list")
+                .short("k")
+                .long("top")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("window")
+                .help("Co-occurrence window for collocations (1 = adjacent bigrams)")
+                .long("window")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("min_freq")
+                .help("Minimum pair frequency for collocations")
+                .long("min-freq")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("stopwords")
+                .help("File of extra stopwords (one per line); always left out of frequency, n-gram, keyword and collocation outputs")
+                .long("stopwords")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("query")
+                .help("Word (or regex with --regex) to search for in 'concordance' mode")
+                .short("q")
+                .long("query")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("is_regex")
+                .help("Treat the concordance query as a regular expression")
+                .long("regex")
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("left_width")
+                .help("Characters of context to the left of the keyword")
+                .long("left")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("right_width")
+                .help("Characters of context to the right of the keyword")
+                .long("right")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("sort_by")
+                .help("Sort concordance lines by their left or right context")
+                .long("sort")
+                .possible_values(&["position", "left", "right"])
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("threshold")
+                .help("Similarity threshold (0-1) for near-duplicate detection")
+                .long("threshold")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("output_dir")
+                .help("Folder to write a deduplicated copy of the corpus to")
+                .short("o")
+                .long("output-dir")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("seed")
+                .help("Random seed for reproducible results")
+                .long("seed")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("raw_lines")
+                .help("Compare raw lines instead of cleaned lines when looking for duplicates")
+                .long("raw")
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("weighting")
+                .help("Term weighting for document vectors")
+                .long("weighting")
+                .possible_values(&["tf", "tfidf"])
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("clusters")
+                .help("Number of clusters (chosen by silhouette score when omitted)")
+                .long("clusters")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("topics")
+                .help("Number of topics for 'topics' mode")
+                .long("topics")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("iterations")
+                .help("Gibbs sampling iterations for 'topics' mode")
+                .long("iterations")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("vocab_size")
+                .help("Target vocabulary size for 'bpe' mode")
+                .long("vocab-size")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("arpa_path")
+                .help("File to save the 'lm' model to in ARPA format")
+                .long("arpa")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("temperature")
+                .help("Sampling temperature for 'generate-text' mode (0 = most frequent word)")
+                .long("temperature")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("prompt")
+                .help("Words to start every generated sentence with")
+                .long("prompt")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("samples")
+                .help("Number of sentences to generate")
+                .long("samples")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("spectrum_max")
+                .help("Largest word frequency listed on its own row of the frequency spectrum")
+                .long("spectrum-max")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("stopword_langs")
+                .help("Bundled stopword lists to use: comma-separated languages, 'all', or 'auto' for the languages detected in each text")
+                .long("stopword-langs")
+                .required(false)
+                .takes_value(true)
+                .use_delimiter(true)
+                .possible_values(&stopword_values)
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::with_name("exclude_stopwords")
+                .help("Leave stopwords out of frequency, n-gram, keyword and collocation outputs")
+                .long("exclude-stopwords")
+                .required(false)
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("stem_lang")
+                .help("Aggregate frequency, n-gram and keyword outputs by stem, using this language's stemmer")
+                .long("stem")
+                .required(false)
+                .takes_value(true)
+                .possible_values(&stem_values)
+                .case_insensitive(true),
+        )
+        .arg(
+            Arg::with_name("lemmas_path")
+                .help("Lemma table (form and lemma per line) to aggregate frequency, n-gram and keyword outputs by")
+                .long("lemmas")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("lexicon_path")
+                .help("Word list (one word per line) to measure coverage against in 'coverage' mode")
+                .long("lexicon")
+                .required(false)
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("encoding")
+                .help("Encoding of the input files (e.g. utf-8, utf-16le, windows-1252, tis-620); detected when omitted")
+                .long("encoding")
+                .required(false)
+                .takes_value(true)
+                .validator(encoding::validate_label),
+        )
+        .arg(
+            Arg::with_name("fix_mojibake")
+                .help("Repair double-encoded UTF-8 (e.g. \"cÃ³mo\" -> \"cómo\") in the input before analysis")
+                .long("fix-mojibake")
+                .required(false)
+                .takes_value(false),
+        )
         .get_matches();
 
     let input_path = matches.value_of("input_path").unwrap_or_default().to_string();
     let mode = matches.value_of("mode").unwrap_or_default().to_string();
-    let is_folder = matches.is_present("is_folder");
+    // An archive is read like a folder of its text members
+    let is_folder = matches.is_present("is_folder") || archive::is_archive(&input_path);
     let n_value = matches
         .value_of("n_value")
         .and_then(|n| n.parse::<usize>().ok());
+    let top_k = matches
+        .value_of("top_k")
+        .and_then(|k| k.parse::<usize>().ok())
+        .unwrap_or(10);
+    let reference_path = matches.value_of("reference_path").map(|p| p.to_string());
+    let window = matches
+        .value_of("window")
+        .and_then(|w| w.parse::<usize>().ok())
+        .unwrap_or(1);
+    let min_freq = matches
+        .value_of("min_freq")
+        .and_then(|f| f.parse::<usize>().ok())
+        .unwrap_or(1);
+    let stopwords_path = matches.value_of("stopwords").map(|p| p.to_string());
+    let query = matches.value_of("query").map(|q| q.to_string());
+    let is_regex = matches.is_present("is_regex");
+    let left_width = matches
+        .value_of("left_width")
+        .and_then(|w| w.parse::<usize>().ok())
+        .unwrap_or(40);
+    let right_width = matches
+        .value_of("right_width")
+        .and_then(|w| w.parse::<usize>().ok())
+        .unwrap_or(40);
+    let sort_by = matches.value_of("sort_by").unwrap_or("position").to_string();
+    let threshold = matches
+        .value_of("threshold")
+        .and_then(|t| t.parse::<f64>().ok())
+        .unwrap_or(0.8);
+    let output_dir = matches.value_of("output_dir").map(|d| d.to_string());
+    let seed = matches
+        .value_of("seed")
+        .and_then(|s| s.parse::<u64>().ok())
+        .unwrap_or(42);
+    let raw_lines = matches.is_present("raw_lines");
+    let weighting = matches.value_of("weighting").unwrap_or("tfidf").to_string();
+    let clusters = matches
+        .value_of("clusters")
+        .and_then(|k| k.parse::<usize>().ok());
+    let topics = matches
+        .value_of("topics")
+        .and_then(|t| t.parse::<usize>().ok())
+        .unwrap_or(5);
+    let iterations = matches
+        .value_of("iterations")
+        .and_then(|i| i.parse::<usize>().ok())
+        .unwrap_or(200);
+    let vocab_size = matches
+        .value_of("vocab_size")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(1000);
+    let arpa_path = matches.value_of("arpa_path").map(|p| p.to_string());
+    let temperature = matches
+        .value_of("temperature")
+        .and_then(|t| t.parse::<f64>().ok())
+        .unwrap_or(1.0);
+    let prompt = matches.value_of("prompt").map(|p| p.to_string());
+    let samples = matches
+        .value_of("samples")
+        .and_then(|s| s.parse::<usize>().ok())
+        .unwrap_or(5);
+    let spectrum_max = matches
+        .value_of("spectrum_max")
+        .and_then(|m| m.parse::<usize>().ok())
+        .unwrap_or(spectrum::SPECTRUM_MAX);
+    let stopword_langs = matches
+        .values_of("stopword_langs")
+        .map_or("auto".to_string(), |langs| langs.collect::<Vec<&str>>().join(","));
+    let exclude_stopwords = matches.is_present("exclude_stopwords");
+    let stem_lang = matches.value_of("stem_lang").map(|l| l.to_string());
+    let lemmas_path = matches.value_of("lemmas_path").map(|p| p.to_string());
+    let lexicon_path = matches.value_of("lexicon_path").map(|p| p.to_string());
+    let encoding = matches.value_of("encoding").map(|e| e.to_string());
+    let fix_mojibake = matches.is_present("fix_mojibake");
 
     AppConfig {
         input_path,
         mode,
         is_folder,
         n_value,
+        top_k,
+        reference_path,
+        window,
+        min_freq,
+        stopwords_path,
+        query,
+        is_regex,
+        left_width,
+        right_width,
+        sort_by,
+        threshold,
+        output_dir,
+        seed,
+        raw_lines,
+        weighting,
+        clusters,
+        topics,
+        iterations,
+        vocab_size,
+        arpa_path,
+        temperature,
+        prompt,
+        samples,
+        spectrum_max,
+        stopword_langs,
+        exclude_stopwords,
+        stem_lang,
+        lemmas_path,
+        lexicon_path,
+        encoding,
+        fix_mojibake,
     }
 }
 
 fn main() {
     let config = configure_app();
 
-    let (counters, lang_infos, file_names) = if config.is_folder {
-        process_folder(&config.input_path, &config.mode, config.n_value)
-    } else {
-        let mut counters = Vec::new();
-        let mut lang_infos = Vec::new();
-        let mut file_names = Vec::new();
-
-        if let Some((counts, lang_info)) = process_file(&config.input_path, &config.mode, config.n_value) {
-            counters.push(counts);
-            lang_infos.push(lang_info);
-            file_names.push(config.input_path.clone());
-        }
+    // Modes that need every input file at once
+    match config.mode.as_str() {
+        "sentences" => return sentences_mode(&config),
+        "keywords" => return keywords_mode(&config),
+        "compare" => return compare_mode(&config),
+        "collocations" => return collocations_mode(&config),
+        "concordance" => return concordance_mode(&config),
+        "dedup" => return dedup_mode(&config),
+        "duplicates" => return duplicates_mode(&config),
+        "cluster" => return cluster_mode(&config),
+        "topics" => return topics_mode(&config),
+        "bpe" => return bpe_mode(&config),
+        "lm" => return lm_mode(&config),
+        "generate-text" => return generate_text_mode(&config),
+        "coverage" => return coverage_mode(&config),
+        "mojibake" => return mojibake_mode(&config),
+        _ => {}
+    }
 
-        (counters, lang_infos, file_names)
+    let reports: Vec<FileReport> = if config.is_folder {
+        process_folder(&config.input_path, &config)
+    } else {
+        process_file(&Input { name: config.input_path.clone(), bytes: None }, &config)
+            .into_iter()
+            .collect()
     };
 
     if config.mode == "generate" {
+        // Corpus-wide statistics are only meaningful for a folder
+        let corpus = if config.is_folder { Some(corpus_counts(&reports, &config)) } else { None };
+
         // Generate the report
-        let report = report_generator(counters, lang_infos, file_names);
+        let mut report = report_generator(&reports, corpus.as_ref(), config.top_k, &config);
+        if config.is_folder {
+            report.push_str(&similarity_section(&reports, &config.weighting));
+        }
 
-        // Define the output HTML file name
-        let output_file_name = if config.is_folder {
-            "report_folder.html"
+        // Define the output file names
+        let (output_file_name, json_file_name) = if config.is_folder {
+            ("report_folder.html", "report_folder.json")
         } else {
-            "report.html"
+            ("report.html", "report.json")
         };
 
         // Write the report to the HTML file
@@ -233,5 +1541,14 @@ fn main() {
         output_file.write_all(report.as_bytes()).expect("Failed to write report to file");
 
         println!("Report saved to {}", output_file_name);
+
+        // Write the same statistics as JSON
+        let counts: Vec<&Counts> = reports.iter().map(|r| &r.counts).collect();
+        let file_names: Vec<String> = reports.iter().map(|r| r.file_name.clone()).collect();
+        let json = generators::generate_json(&counts, &file_names, corpus.as_ref());
+        let mut json_file = File::create(json_file_name).expect("Failed to create output file");
+        json_file.write_all(json.as_bytes()).expect("Failed to write report to file");
+
+        println!("Statistics saved to {}", json_file_name);
     }
 }
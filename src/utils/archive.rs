@@ -0,0 +1,209 @@
+use std::fs::File;
+use std::io::{self, Cursor, Read};
+use std::path::{Component, Path};
+
+use crate::compression;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Zip,
+    Tar,
+}
+
+fn kind(file_path: &str) -> Option<Kind> {
+    // By extension; a tar file may also be compressed (.tar.gz, .tgz, .tar.xz, ...)
+    let lower = compression::strip_extension(file_path).to_lowercase();
+    if lower.ends_with(".zip") {
+        Some(Kind::Zip)
+    } else if lower.ends_with(".tar") || lower.ends_with(".tgz") {
+        Some(Kind::Tar)
+    } else {
+        None
+    }
+}
+
+pub fn is_archive(file_path: &str) -> bool {
+    kind(file_path).is_some() && Path::new(file_path).is_file()
+}
+
+pub fn split_member(file_path: &str) -> Option<(&str, &str)> {
+    // Member paths are written as "<archive>/<path in the archive>", e.g. "corpus.zip/docs/a.txt"
+    file_path
+        .match_indices('/')
+        .map(|(i, _)| (&file_path[..i], &file_path[i + 1..]))
+        .find(|(archive, _)| is_archive(archive))
+}
+
+pub fn output_name(file_path: &str) -> String {
+    // Name for files written next to an input, which cannot go inside an archive:
+    // "corpus.zip/docs/a.txt" -> "corpus.zip_docs_a.txt"
+    match split_member(file_path) {
+        Some((archive, member)) => format!("{}_{}", archive, member.replace('/', "_")),
+        None => file_path.to_string(),
+    }
+}
+
+fn is_enclosed(path: &Path) -> bool {
+    // Member paths are joined into output folders (dedup --output-dir), so they must stay inside them
+    path.components().all(|c| !matches!(c, Component::ParentDir | Component::RootDir | Component::Prefix(_)))
+}
+
+fn tar_entries<R: Read>(entries: tar::Entries<'_, R>, mut visit: impl FnMut(&str, &mut dyn Read) -> bool) -> io::Result<()> {
+    // Calls visit with the path and content of every regular file until it returns false
+    for entry in entries {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path()?;
+        if !is_enclosed(&path) {
+            eprintln!("Warning: skipping archive member {} with a path outside the archive", path.display());
+            continue;
+        }
+        let path = path.to_string_lossy().trim_start_matches("./").to_string();
+        if !visit(&path, &mut entry) {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn walk_tar(archive_path: &str, visit: impl FnMut(&str, &mut dyn Read) -> bool) -> io::Result<()> {
+    // A plain tar is read with seeks over the content of skipped members; a compressed one is streamed
+    if compression::detect_file(archive_path)? == compression::Compression::None {
+        let mut archive = tar::Archive::new(File::open(archive_path)?);
+        tar_entries(archive.entries_with_seek()?, visit)
+    } else {
+        let mut archive = tar::Archive::new(compression::open(archive_path)?);
+        tar_entries(archive.entries()?, visit)
+    }
+}
+
+pub fn members(archive_path: &str, keep: impl Fn(&str) -> bool) -> io::Result<Vec<(String, Vec<u8>)>> {
+    // Paths and contents of the regular files for which keep is true, in archive order, in a single
+    // pass over the archive; compressed members (e.g. "a.txt.gz") are decompressed
+    let mut found = Vec::new();
+    match kind(archive_path) {
+        Some(Kind::Zip) => {
+            let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+            for i in 0..archive.len() {
+                let mut file = archive.by_index(i)?;
+                if !file.is_file() {
+                    continue;
+                }
+                let name = match file.enclosed_name() {
+                    Some(path) => path.to_string_lossy().into_owned(),
+                    None => {
+                        eprintln!("Warning: skipping archive member {} with a path outside the archive", file.name());
+                        continue;
+                    }
+                };
+                if keep(&name) {
+                    let mut bytes = Vec::new();
+                    file.read_to_end(&mut bytes)?;
+                    found.push((name, bytes));
+                }
+            }
+        }
+        Some(Kind::Tar) => {
+            let mut result = Ok(());
+            walk_tar(archive_path, |path, content| {
+                if !keep(path) {
+                    return true;
+                }
+                let mut bytes = Vec::new();
+                result = content.read_to_end(&mut bytes).map(|_| found.push((path.to_string(), bytes)));
+                result.is_ok()
+            })?;
+            result?;
+        }
+        None => return Err(io::Error::new(io::ErrorKind::InvalidInput, "not a zip or tar archive")),
+    }
+
+    found
+        .into_iter()
+        .map(|(name, bytes)| {
+            let content = decompress_member(bytes, &name)?;
+            Ok((name, content))
+        })
+        .collect()
+}
+
+pub fn read_member(archive_path: &str, member: &str) -> io::Result<Vec<u8>> {
+    // Content of one member, for a single path such as "corpus.zip/docs/a.txt"; use members to read many
+    members(archive_path, |path| path == member)?
+        .pop()
+        .map(|(_, bytes)| bytes)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("no member {} in {}", member, archive_path)))
+}
+
+fn decompress_member(bytes: Vec<u8>, member: &str) -> io::Result<Vec<u8>> {
+    // Decompressed content when the member itself is compressed (e.g. "a.txt.gz")
+    let mut content = Vec::new();
+    compression::decompress(Cursor::new(bytes), member)?.read_to_end(&mut content)?;
+    Ok(content)
+}
+
+#[test]
+fn test_archives() {
+    use std::io::Write;
+
+    let dir = std::env::temp_dir().join(format!("corpux_archive_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let zip_path = dir.join("corpus.zip").to_string_lossy().into_owned();
+    let tar_path = dir.join("corpus.tar.gz").to_string_lossy().into_owned();
+
+    let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+    zip.add_directory("docs/", zip::write::SimpleFileOptions::default()).unwrap();
+    zip.start_file("docs/a.txt", zip::write::SimpleFileOptions::default()).unwrap();
+    zip.write_all(b"first\n").unwrap();
+    zip.finish().unwrap();
+
+    let gz = flate2::write::GzEncoder::new(File::create(&tar_path).unwrap(), flate2::Compression::default());
+    let mut tar = tar::Builder::new(gz);
+    for (name, text) in [("./a.txt", "alpha\n"), ("b.txt", "beta\n")] {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(text.len() as u64);
+        header.set_cksum();
+        tar.append_data(&mut header, name, text.as_bytes()).unwrap();
+    }
+    tar.into_inner().unwrap().finish().unwrap();
+
+    let all = |_: &str| true;
+    assert_eq!(members(&zip_path, all).unwrap(), vec![("docs/a.txt".to_string(), b"first\n".to_vec())]);
+    let tar_members = members(&tar_path, all).unwrap();
+    assert_eq!(tar_members.iter().map(|(name, _)| name.as_str()).collect::<Vec<_>>(), vec!["a.txt", "b.txt"]);
+    assert_eq!(members(&tar_path, |path| path == "b.txt").unwrap(), vec![("b.txt".to_string(), b"beta\n".to_vec())]);
+    assert_eq!(read_member(&zip_path, "docs/a.txt").unwrap(), b"first\n");
+    assert_eq!(read_member(&tar_path, "b.txt").unwrap(), b"beta\n");
+    assert!(read_member(&tar_path, "c.txt").is_err());
+
+    let member = format!("{}/docs/a.txt", zip_path);
+    assert_eq!(split_member(&member), Some((zip_path.as_str(), "docs/a.txt")));
+    assert_eq!(output_name(&member), format!("{}_docs_a.txt", zip_path));
+    assert_eq!(split_member(&zip_path), None);
+
+    // Members that would be written outside an output folder are skipped
+    let bad_zip = dir.join("bad.zip").to_string_lossy().into_owned();
+    let mut zip = zip::ZipWriter::new(File::create(&bad_zip).unwrap());
+    for name in ["../../escaped.txt", "/abs.txt", "ok.txt"] {
+        zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"text\n").unwrap();
+    }
+    zip.finish().unwrap();
+    assert_eq!(members(&bad_zip, all).unwrap(), vec![("ok.txt".to_string(), b"text\n".to_vec())]);
+
+    let bad_tar = dir.join("bad.tar").to_string_lossy().into_owned();
+    let mut tar = tar::Builder::new(File::create(&bad_tar).unwrap());
+    for name in ["../escaped.txt", "/abs.txt", "ok.txt"] {
+        // Written into the header directly, since the builder refuses such paths
+        let mut header = tar::Header::new_gnu();
+        header.as_gnu_mut().unwrap().name[..name.len()].copy_from_slice(name.as_bytes());
+        header.set_size(5);
+        header.set_cksum();
+        tar.append(&header, &b"text\n"[..]).unwrap();
+    }
+    tar.into_inner().unwrap();
+    assert_eq!(members(&bad_tar, all).unwrap(), vec![("ok.txt".to_string(), b"text\n".to_vec())]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use crate::lm::{KneserNey, LanguageModel, BOS, UNK};
+
+// log10 probability written for <s>, which is never predicted
+const NEVER: f64 = -99.0;
+
+pub struct ArpaModel {
+    order: usize,
+    // n-gram -> (log10 probability, log10 backoff weight)
+    entries: HashMap<Vec<String>, (f64, f64)>,
+}
+
+pub fn write_arpa(model: &KneserNey) -> String {
+    // Interpolated probabilities of every seen n-gram with the interpolation weight of every seen
+    // context as its backoff, so that backing off gives the same scores as the interpolated model
+    let order = model.order();
+    let mut sections: Vec<Vec<String>> = Vec::with_capacity(order);
+
+    for k in 1..=order {
+        let mut grams: Vec<Vec<String>> = model.ngrams(k).into_iter().cloned().collect();
+        if k == 1 {
+            grams.push(vec![BOS.to_string()]);
+            grams.push(vec![UNK.to_string()]);
+            grams.sort();
+        }
+
+        let lines = grams
+            .iter()
+            .map(|gram| {
+                let (context, word) = gram.split_at(gram.len() - 1);
+                let prob = if word[0] == BOS { NEVER } else { model.prob(context, &word[0]).log10() };
+                match model.backoff(gram).filter(|_| k < order) {
+                    Some(bow) => format!("{:.6}\t{}\t{:.6}", prob, gram.join(" "), bow.log10()),
+                    None => format!("{:.6}\t{}", prob, gram.join(" ")),
+                }
+            })
+            .collect();
+        sections.push(lines);
+    }
+
+    let mut arpa = String::from("\\data\\\n");
+    for (k, lines) in sections.iter().enumerate() {
+        arpa.push_str(&format!("ngram {}={}\n", k + 1, lines.len()));
+    }
+    for (k, lines) in sections.iter().enumerate() {
+        arpa.push_str(&format!("\n\\{}-grams:\n", k + 1));
+        for line in lines {
+            arpa.push_str(line);
+            arpa.push('\n');
+        }
+    }
+    arpa.push_str("\n\\end\\\n");
+    arpa
+}
+
+pub fn read_arpa(text: &str) -> ArpaModel {
+    // Only the n-gram sections are needed; the \data\ header counts are not checked
+    let mut entries = HashMap::new();
+    let mut order = 0;
+    let mut current = 0;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line == "\\data\\" || line.starts_with("ngram ") {
+            continue;
+        }
+        if line == "\\end\\" {
+            break;
+        }
+        if let Some(k) = line.strip_prefix('\\').and_then(|l| l.strip_suffix("-grams:")) {
+            current = k.parse::<usize>().expect("Invalid n-gram section header in the ARPA file.");
+            order = order.max(current);
+            continue;
+        }
+        if current == 0 {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < current + 1 {
+            panic!("Invalid ARPA line: {}", line);
+        }
+        let prob = fields[0].parse::<f64>().unwrap_or_else(|_| panic!("Invalid ARPA line: {}", line));
+        let gram: Vec<String> = fields[1..=current].iter().map(|w| w.to_string()).collect();
+        let bow = match fields.get(current + 1) {
+            Some(b) => b.parse::<f64>().unwrap_or_else(|_| panic!("Invalid ARPA line: {}", line)),
+            None => 0.0,
+        };
+        entries.insert(gram, (prob, bow));
+    }
+
+    ArpaModel { order: order.max(1), entries }
+}
+
+impl LanguageModel for ArpaModel {
+    fn order(&self) -> usize {
+        self.order
+    }
+
+    fn log10_prob(&self, context: &[String], word: &str) -> f64 {
+        // Longest matching n-gram, plus the backoff weights of the longer contexts that were skipped
+        let context = &context[context.len().saturating_sub(self.order - 1)..];
+        let mut backoff = 0.0;
+        for start in 0..=context.len() {
+            let mut gram = context[start..].to_vec();
+            gram.push(word.to_string());
+            if let Some((prob, _)) = self.entries.get(&gram) {
+                return prob + backoff;
+            }
+            if let Some((_, bow)) = self.entries.get(&context[start..]) {
+                backoff += bow;
+            }
+        }
+        match self.entries.get(&vec![UNK.to_string()]) {
+            Some((prob, _)) => prob + backoff,
+            None => NEVER,
+        }
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.entries.contains_key(&vec![word.to_string()])
+    }
+}
+
+#[test]
+fn test_arpa_round_trip() {
+    use crate::lm::score_line;
+
+    let lines: Vec<String> = ["the cat sat on the mat", "the dog sat on the log", "a cat and a dog"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let model = KneserNey::train(&lines, 3);
+    let text = write_arpa(&model);
+    assert!(text.starts_with("\\data\\\nngram 1=12\n"));
+    assert!(text.contains("-99.000000\t<s>\t"));
+
+    // Backing off through the ARPA file reproduces the interpolated scores
+    let arpa = read_arpa(&text);
+    assert_eq!(arpa.order(), 3);
+    for line in ["the cat sat on the log", "log the on a sat mat", "the zebra"] {
+        let expected = score_line(&model, line);
+        let actual = score_line(&arpa, line);
+        assert_eq!(actual.oov, expected.oov);
+        assert!((actual.log10_prob - expected.log10_prob).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,153 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+// Marks the last symbol of a word, so that "er</w>" (word-final) and "er" (word-internal) stay apart
+pub const END_OF_WORD: &str = "</w>";
+
+type Pair = (String, String);
+
+pub struct Bpe {
+    // Learned merges in order, and the vocabulary in id order (base symbols first)
+    pub merges: Vec<Pair>,
+    pub vocab: Vec<String>,
+    ranks: HashMap<Pair, usize>,
+}
+
+fn split_word(word: &str) -> Vec<String> {
+    let chars: Vec<char> = word.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, c)| if i + 1 == chars.len() { format!("{}{}", c, END_OF_WORD) } else { c.to_string() })
+        .collect()
+}
+
+fn merge_pair(symbols: &[String], pair: &Pair) -> Vec<String> {
+    let mut merged = Vec::with_capacity(symbols.len());
+    let mut i = 0;
+    while i < symbols.len() {
+        if i + 1 < symbols.len() && symbols[i] == pair.0 && symbols[i + 1] == pair.1 {
+            merged.push(format!("{}{}", pair.0, pair.1));
+            i += 2;
+        } else {
+            merged.push(symbols[i].clone());
+            i += 1;
+        }
+    }
+    merged
+}
+
+fn pairs(symbols: &[String]) -> impl Iterator<Item = Pair> + '_ {
+    symbols.windows(2).map(|w| (w[0].clone(), w[1].clone()))
+}
+
+pub fn train(freq: &HashMap<String, usize>, vocab_size: usize) -> Bpe {
+    // Repeatedly merge the most frequent adjacent symbol pair (ties go to the smallest pair)
+    // until the vocabulary reaches vocab_size or no pair is left.
+    // Pair counts are updated incrementally and the best pair comes from a lazily invalidated heap
+    let mut word_list: Vec<(&String, &usize)> = freq.iter().collect();
+    word_list.sort();
+    let mut words: Vec<(Vec<String>, usize)> = word_list.iter().map(|(w, &c)| (split_word(w), c)).collect();
+
+    let mut vocab: Vec<String> = words.iter().flat_map(|(s, _)| s.iter().cloned()).collect::<HashSet<String>>().into_iter().collect();
+    vocab.sort();
+    let mut known: HashSet<String> = vocab.iter().cloned().collect();
+
+    let mut counts: HashMap<Pair, usize> = HashMap::new();
+    let mut occurrences: HashMap<Pair, HashSet<usize>> = HashMap::new();
+    for (i, (symbols, count)) in words.iter().enumerate() {
+        for pair in pairs(symbols) {
+            *counts.entry(pair.clone()).or_insert(0) += count;
+            occurrences.entry(pair).or_default().insert(i);
+        }
+    }
+    let mut heap: BinaryHeap<(usize, Reverse<Pair>)> = counts.iter().map(|(p, &c)| (c, Reverse(p.clone()))).collect();
+
+    let mut merges = Vec::new();
+    while vocab.len() < vocab_size {
+        let best = loop {
+            match heap.pop() {
+                // Skip entries whose count has changed since they were pushed
+                Some((count, Reverse(pair))) if counts.get(&pair) == Some(&count) && count > 0 => break Some(pair),
+                Some(_) => continue,
+                None => break None,
+            }
+        };
+        let Some(best) = best else { break };
+
+        let mut changed: HashSet<Pair> = HashSet::new();
+        let mut indices: Vec<usize> = occurrences.remove(&best).unwrap_or_default().into_iter().collect();
+        indices.sort();
+        for i in indices {
+            let (symbols, count) = &words[i];
+            for pair in pairs(symbols) {
+                if let Some(c) = counts.get_mut(&pair) {
+                    *c -= count;
+                }
+                changed.insert(pair);
+            }
+            let merged = merge_pair(symbols, &best);
+            for pair in pairs(&merged) {
+                *counts.entry(pair.clone()).or_insert(0) += count;
+                occurrences.entry(pair.clone()).or_default().insert(i);
+                changed.insert(pair);
+            }
+            words[i].0 = merged;
+        }
+        counts.remove(&best);
+        changed.remove(&best);
+        for pair in changed {
+            if let Some(&c) = counts.get(&pair) {
+                if c > 0 {
+                    heap.push((c, Reverse(pair)));
+                }
+            }
+        }
+
+        // Different merges can spell the same symbol; it only enters the vocabulary once
+        let symbol = format!("{}{}", best.0, best.1);
+        if known.insert(symbol.clone()) {
+            vocab.push(symbol);
+        }
+        merges.push(best);
+    }
+
+    let ranks = merges.iter().enumerate().map(|(i, p)| (p.clone(), i)).collect();
+    Bpe { merges, vocab, ranks }
+}
+
+impl Bpe {
+    pub fn encode_word(&self, word: &str) -> Vec<String> {
+        // Apply the learned merges to a word, earliest merge first
+        let mut symbols = split_word(word);
+        loop {
+            let best = pairs(&symbols)
+                .filter_map(|p| self.ranks.get(&p).map(|&rank| (rank, p)))
+                .min();
+            match best {
+                Some((_, pair)) => symbols = merge_pair(&symbols, &pair),
+                None => return symbols,
+            }
+        }
+    }
+}
+
+#[test]
+fn test_bpe_training() {
+    let mut freq = HashMap::new();
+    freq.insert("low".to_string(), 5);
+    freq.insert("lower".to_string(), 2);
+    freq.insert("newest".to_string(), 6);
+    freq.insert("widest".to_string(), 3);
+
+    let base = train(&freq, 0).vocab.len();
+    let bpe = train(&freq, base + 4);
+    assert_eq!(bpe.vocab.len(), base + 4);
+    // "es" and "st</w>" both occur 9 times (newest, widest); the smaller pair wins, then "est</w>", then "lo" (7 times)
+    assert_eq!(bpe.merges[0], ("e".to_string(), "s".to_string()));
+    assert_eq!(bpe.merges[1], ("es".to_string(), "t</w>".to_string()));
+    assert_eq!(bpe.merges[2], ("l".to_string(), "o".to_string()));
+
+    assert_eq!(bpe.encode_word("lowest"), vec!["lo", "w", "est</w>"]);
+    assert_eq!(bpe.encode_word("x"), vec!["x</w>"]);
+}
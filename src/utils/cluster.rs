@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+
+use crate::rng::Rng;
+
+// Upper bound on k-means iterations; assignments usually settle long before this
+const MAX_ITERATIONS: usize = 100;
+// Largest k tried when the number of clusters is chosen by silhouette score
+pub const MAX_AUTO_K: usize = 10;
+
+#[derive(Debug)]
+pub struct Clustering {
+    pub k: usize,
+    pub assignments: Vec<usize>,
+    pub centroids: Vec<HashMap<String, f64>>,
+    pub silhouette: f64,
+}
+
+fn dot(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    small.iter().filter_map(|(w, x)| large.get(w).map(|y| x * y)).fold(0.0, |acc, v| acc + v)
+}
+
+pub fn normalize(vector: &HashMap<String, f64>) -> HashMap<String, f64> {
+    // Unit length, so that the dot product of two vectors is their cosine similarity
+    let norm = vector.values().map(|v| v * v).sum::<f64>().sqrt();
+    if norm == 0.0 {
+        return vector.clone();
+    }
+    vector.iter().map(|(w, v)| (w.clone(), v / norm)).collect()
+}
+
+fn distance(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    // Cosine distance between unit vectors
+    (1.0 - dot(a, b)).max(0.0)
+}
+
+fn initial_centroids(vectors: &[HashMap<String, f64>], k: usize, rng: &mut Rng) -> Vec<HashMap<String, f64>> {
+    // k-means++: each new centre is drawn with probability proportional to its squared distance
+    let mut centroids = vec![vectors[rng.below(vectors.len())].clone()];
+
+    while centroids.len() < k {
+        let weights: Vec<f64> = vectors
+            .iter()
+            .map(|v| centroids.iter().map(|c| distance(v, c)).fold(f64::MAX, f64::min).powi(2))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total == 0.0 {
+            // Every point is already a centre
+            centroids.push(vectors[rng.below(vectors.len())].clone());
+            continue;
+        }
+
+        let mut target = rng.next_f64() * total;
+        let mut chosen = vectors.len() - 1;
+        for (i, w) in weights.iter().enumerate() {
+            if target < *w {
+                chosen = i;
+                break;
+            }
+            target -= w;
+        }
+        centroids.push(vectors[chosen].clone());
+    }
+
+    centroids
+}
+
+fn nearest(vector: &HashMap<String, f64>, centroids: &[HashMap<String, f64>]) -> usize {
+    let mut best = 0;
+    let mut best_sim = f64::MIN;
+    for (c, centroid) in centroids.iter().enumerate() {
+        let sim = dot(vector, centroid);
+        if sim > best_sim {
+            best = c;
+            best_sim = sim;
+        }
+    }
+    best
+}
+
+pub fn kmeans(vectors: &[HashMap<String, f64>], k: usize, seed: u64) -> Clustering {
+    // Spherical k-means over unit vectors with k-means++ initialisation
+    let k = k.clamp(1, vectors.len().max(1));
+    let mut rng = Rng::new(seed);
+    let mut centroids = if vectors.is_empty() { Vec::new() } else { initial_centroids(vectors, k, &mut rng) };
+    let mut assignments = vec![usize::MAX; vectors.len()];
+
+    for _ in 0..MAX_ITERATIONS {
+        let next: Vec<usize> = vectors.iter().map(|v| nearest(v, &centroids)).collect();
+        if next == assignments {
+            break;
+        }
+        assignments = next;
+
+        // New centroid = normalised sum of its members
+        let mut sums: Vec<HashMap<String, f64>> = vec![HashMap::new(); k];
+        for (v, &c) in vectors.iter().zip(&assignments) {
+            for (w, x) in v {
+                *sums[c].entry(w.clone()).or_insert(0.0) += x;
+            }
+        }
+        for (c, sum) in sums.iter().enumerate() {
+            if sum.is_empty() {
+                // Re-seed an empty cluster with the point that fits its own cluster worst
+                let worst = (0..vectors.len())
+                    .min_by(|&a, &b| {
+                        dot(&vectors[a], &centroids[assignments[a]]).total_cmp(&dot(&vectors[b], &centroids[assignments[b]]))
+                    })
+                    .unwrap();
+                centroids[c] = vectors[worst].clone();
+            } else {
+                centroids[c] = normalize(sum);
+            }
+        }
+    }
+
+    let silhouette = silhouette(vectors, &assignments, k);
+    Clustering { k, assignments, centroids, silhouette }
+}
+
+pub fn silhouette(vectors: &[HashMap<String, f64>], assignments: &[usize], k: usize) -> f64 {
+    // Mean silhouette (b - a) / max(a, b) with cosine distance; points alone in their cluster score 0
+    if k < 2 || vectors.len() < 2 {
+        return 0.0;
+    }
+
+    let mut total = 0.0;
+    for (i, v) in vectors.iter().enumerate() {
+        let mut sums = vec![0.0; k];
+        let mut sizes = vec![0; k];
+        for (j, u) in vectors.iter().enumerate() {
+            if i != j {
+                sums[assignments[j]] += distance(v, u);
+                sizes[assignments[j]] += 1;
+            }
+        }
+
+        let own = assignments[i];
+        if sizes[own] == 0 {
+            continue;
+        }
+        let a = sums[own] / sizes[own] as f64;
+        let b = (0..k)
+            .filter(|&c| c != own && sizes[c] > 0)
+            .map(|c| sums[c] / sizes[c] as f64)
+            .fold(f64::MAX, f64::min);
+        if b == f64::MAX {
+            continue;
+        }
+        let max = a.max(b);
+        if max > 0.0 {
+            total += (b - a) / max;
+        }
+    }
+
+    total / vectors.len() as f64
+}
+
+pub fn choose_k(vectors: &[HashMap<String, f64>], max_k: usize, seed: u64) -> Clustering {
+    // Try k = 2..=max_k and keep the clustering with the best silhouette score
+    let upper = max_k.min(vectors.len().saturating_sub(1));
+    if upper < 2 {
+        return kmeans(vectors, 1, seed);
+    }
+
+    (2..=upper)
+        .map(|k| kmeans(vectors, k, seed))
+        .max_by(|a, b| a.silhouette.total_cmp(&b.silhouette))
+        .unwrap()
+}
+
+pub fn top_terms(centroid: &HashMap<String, f64>, n: usize) -> Vec<String> {
+    // Heaviest words of a cluster centroid
+    let mut terms: Vec<(&String, &f64)> = centroid.iter().collect();
+    terms.sort_by(|a, b| b.1.total_cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    terms.into_iter().take(n).map(|(w, _)| w.clone()).collect()
+}
+
+#[test]
+fn test_kmeans_clusters() {
+    let docs = [
+        vec![("cat", 3.0), ("dog", 1.0)],
+        vec![("cat", 2.0), ("dog", 2.0)],
+        vec![("stock", 3.0), ("bond", 1.0)],
+        vec![("stock", 1.0), ("bond", 3.0)],
+    ];
+    let vectors: Vec<HashMap<String, f64>> = docs
+        .iter()
+        .map(|d| normalize(&d.iter().map(|(w, x)| (w.to_string(), *x)).collect()))
+        .collect();
+
+    let clustering = choose_k(&vectors, MAX_AUTO_K, 1);
+
+    assert_eq!(clustering.k, 2);
+    assert_eq!(clustering.assignments[0], clustering.assignments[1]);
+    assert_eq!(clustering.assignments[2], clustering.assignments[3]);
+    assert_ne!(clustering.assignments[0], clustering.assignments[2]);
+    assert!(clustering.silhouette > 0.5);
+
+    let pets = clustering.assignments[0];
+    assert_eq!(top_terms(&clustering.centroids[pets], 1), vec!["cat"]);
+}
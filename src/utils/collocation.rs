@@ -0,0 +1,137 @@
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone)]
+pub struct Collocation {
+    pub first: String,
+    pub second: String,
+    pub freq: usize,
+    pub pmi: f64,
+    pub t_score: f64,
+    pub dice: f64,
+    pub log_likelihood: f64,
+    pub mi3: f64,
+}
+
+// Association measures a collocation table can be ranked by
+pub const MEASURES: [&str; 5] = ["pmi", "t_score", "dice", "log_likelihood", "mi3"];
+
+impl Collocation {
+    pub fn score(&self, measure: &str) -> f64 {
+        match measure {
+            "pmi" => self.pmi,
+            "t_score" => self.t_score,
+            "dice" => self.dice,
+            "log_likelihood" => self.log_likelihood,
+            "mi3" => self.mi3,
+            _ => self.freq as f64,
+        }
+    }
+}
+
+fn log_likelihood(o11: f64, f1: f64, f2: f64, n: f64) -> f64 {
+    // G² over the 2x2 contingency table of the pair; cells that go negative with a window are clamped
+    let observed = [
+        o11,
+        (f1 - o11).max(0.0),
+        (f2 - o11).max(0.0),
+        (n - f1 - f2 + o11).max(0.0),
+    ];
+    let expected = [
+        f1 * f2 / n,
+        f1 * (n - f2) / n,
+        (n - f1) * f2 / n,
+        (n - f1) * (n - f2) / n,
+    ];
+    2.0 * observed
+        .iter()
+        .zip(expected.iter())
+        .filter(|(o, e)| **o > 0.0 && **e > 0.0)
+        .map(|(o, e)| o * (o / e).ln())
+        .sum::<f64>()
+}
+
+pub fn collocations(
+    units: &[Vec<String>],
+    window: usize,
+    min_freq: usize,
+    stopwords: &HashSet<String>,
+) -> Vec<Collocation> {
+    // Count ordered word pairs up to `window` words apart (1 = adjacent bigrams) inside each unit
+    // (sentence or line) and score them with the usual association measures
+    let window = window.max(1);
+    let mut word_freq: HashMap<&str, usize> = HashMap::new();
+    let mut pair_freq: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut n = 0;
+
+    for unit in units {
+        for (i, word) in unit.iter().enumerate() {
+            n += 1;
+            *word_freq.entry(word.as_str()).or_insert(0) += 1;
+            if stopwords.contains(word) {
+                continue;
+            }
+            for other in unit.iter().skip(i + 1).take(window) {
+                if !stopwords.contains(other) {
+                    *pair_freq.entry((word.as_str(), other.as_str())).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    let n_f = n as f64;
+    pair_freq
+        .into_iter()
+        .filter(|(_, f)| *f >= min_freq)
+        .map(|((first, second), f)| {
+            let o = f as f64;
+            let f1 = word_freq[first] as f64;
+            let f2 = word_freq[second] as f64;
+            // Expected co-occurrences grow with the size of the window (Evert, 2008)
+            let e = f1 * f2 * window as f64 / n_f;
+
+            Collocation {
+                first: first.to_string(),
+                second: second.to_string(),
+                freq: f,
+                pmi: (o / e).log2(),
+                t_score: (o - e) / o.sqrt(),
+                dice: 2.0 * o / (f1 + f2),
+                log_likelihood: log_likelihood(o, f1, f2, n_f),
+                mi3: (o.powi(3) / e).log2(),
+            }
+        })
+        .collect()
+}
+
+pub fn rank_by(colls: &mut [Collocation], measure: &str) {
+    // Strongest association first, ties broken by the pair itself
+    colls.sort_by(|a, b| {
+        b.score(measure)
+            .total_cmp(&a.score(measure))
+            .then_with(|| (&a.first, &a.second).cmp(&(&b.first, &b.second)))
+    });
+}
+
+#[test]
+fn test_collocations() {
+    let units: Vec<Vec<String>> = ["new york is big", "new york is old", "the city is new"]
+        .iter()
+        .map(|s| s.split(' ').map(|w| w.to_string()).collect())
+        .collect();
+
+    let mut colls = collocations(&units, 1, 2, &HashSet::new());
+    rank_by(&mut colls, "pmi");
+
+    // Only pairs seen at least twice survive
+    assert_eq!(colls.len(), 2);
+    assert_eq!((colls[0].first.as_str(), colls[0].second.as_str()), ("new", "york"));
+    // f(new) = 3, f(york) = 2, f(new york) = 2 over 12 tokens
+    assert!((colls[0].pmi - (2.0 * 12.0 / 6.0f64).log2()).abs() < 1e-12);
+    assert_eq!(colls[0].dice, 0.8);
+
+    let mut stopwords = HashSet::new();
+    stopwords.insert("is".to_string());
+    let colls = collocations(&units, 2, 1, &stopwords);
+    assert!(colls.iter().all(|c| c.first != "is" && c.second != "is"));
+    assert!(colls.iter().any(|c| c.first == "york" && c.second == "big"));
+}
@@ -0,0 +1,125 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+
+use bzip2::read::MultiBzDecoder;
+use flate2::read::MultiGzDecoder;
+use xz2::read::XzDecoder;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    None,
+    Gzip,
+    Bzip2,
+    Xz,
+    Zstd,
+}
+
+// Magic bytes and file extensions of the supported formats
+const FORMATS: [(Compression, &[u8], &str); 4] = [
+    (Compression::Gzip, &[0x1F, 0x8B], "gz"),
+    (Compression::Bzip2, b"BZh", "bz2"),
+    (Compression::Xz, &[0xFD, b'7', b'z', b'X', b'Z', 0x00], "xz"),
+    (Compression::Zstd, &[0x28, 0xB5, 0x2F, 0xFD], "zst"),
+];
+
+pub fn from_extension(file_path: &str) -> Compression {
+    FORMATS
+        .iter()
+        .find(|(_, _, ext)| file_path.to_lowercase().ends_with(&format!(".{}", ext)))
+        .map_or(Compression::None, |(c, _, _)| *c)
+}
+
+pub fn strip_extension(file_path: &str) -> &str {
+    // "corpus.txt.gz" -> "corpus.txt"; plain paths are returned as they are
+    match from_extension(file_path) {
+        Compression::None => file_path,
+        _ => &file_path[..file_path.rfind('.').unwrap()],
+    }
+}
+
+pub fn detect(header: &[u8], file_path: &str) -> Compression {
+    // Magic bytes first, then the extension
+    match FORMATS.iter().find(|(_, magic, _)| header.starts_with(magic)) {
+        Some((c, _, _)) => *c,
+        None => from_extension(file_path),
+    }
+}
+
+pub fn open(file_path: &str) -> io::Result<Box<dyn Read>> {
+    // A reader that decompresses the file while it is read
+    decompress(BufReader::new(File::open(file_path)?), file_path)
+}
+
+pub fn decompress<R: Read + 'static>(mut reader: R, file_path: &str) -> io::Result<Box<dyn Read>> {
+    // Wraps the reader in the decoder for its format; file_path is only used for its extension
+    let mut header = [0; 6];
+    let n = read_header(&mut reader, &mut header)?;
+    let reader = io::Cursor::new(header[..n].to_vec()).chain(reader);
+
+    Ok(match detect(&header[..n], file_path) {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(MultiGzDecoder::new(reader)),
+        Compression::Bzip2 => Box::new(MultiBzDecoder::new(reader)),
+        Compression::Xz => Box::new(XzDecoder::new_multi_decoder(reader)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(reader)?),
+    })
+}
+
+pub fn detect_file(file_path: &str) -> io::Result<Compression> {
+    let mut header = [0; 6];
+    let n = read_header(&mut File::open(file_path)?, &mut header)?;
+    Ok(detect(&header[..n], file_path))
+}
+
+fn read_header(reader: &mut impl Read, header: &mut [u8]) -> io::Result<usize> {
+    // Fills the header unless the file is shorter
+    let mut n = 0;
+    while n < header.len() {
+        match reader.read(&mut header[n..])? {
+            0 => break,
+            k => n += k,
+        }
+    }
+    Ok(n)
+}
+
+pub fn read_bytes(file_path: &str) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open(file_path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[test]
+fn test_decompression() {
+    use std::io::Write;
+
+    let text = "first line\nsecond line\n";
+    let dir = std::env::temp_dir().join(format!("corpux_compression_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let mut gz = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    gz.write_all(text.as_bytes()).unwrap();
+    let mut bz = bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+    bz.write_all(text.as_bytes()).unwrap();
+    let mut xz = xz2::write::XzEncoder::new(Vec::new(), 6);
+    xz.write_all(text.as_bytes()).unwrap();
+    let files = [
+        ("a.txt", text.as_bytes().to_vec()),
+        ("b.txt.gz", gz.finish().unwrap()),
+        ("c.txt.bz2", bz.finish().unwrap()),
+        ("d.txt.xz", xz.finish().unwrap()),
+        ("e.txt.zst", zstd::encode_all(text.as_bytes(), 3).unwrap()),
+        // Compressed content is found by its magic bytes whatever the name
+        ("f.txt", zstd::encode_all(text.as_bytes(), 3).unwrap()),
+    ];
+    for (name, bytes) in &files {
+        let path = dir.join(name).to_string_lossy().into_owned();
+        std::fs::write(&path, bytes).unwrap();
+        assert_eq!(read_bytes(&path).unwrap(), text.as_bytes(), "{}", name);
+    }
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(strip_extension("corpus/a.txt.gz"), "corpus/a.txt");
+    assert_eq!(strip_extension("corpus/a.txt"), "corpus/a.txt");
+    assert_eq!(detect(b"BZh91AY", "x.txt"), Compression::Bzip2);
+}
@@ -0,0 +1,87 @@
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+pub struct KwicLine {
+    pub file: String,
+    pub line: usize,
+    pub left: String,
+    pub keyword: String,
+    pub right: String,
+}
+
+pub fn query_regex(query: &str, is_regex: bool) -> Regex {
+    // A plain query matches the whole word, ignoring case; a regex query is used as given
+    if is_regex {
+        Regex::new(query).expect("Invalid regular expression for the concordance query.")
+    } else {
+        Regex::new(&format!(r"(?i)\b{}\b", regex::escape(query))).unwrap()
+    }
+}
+
+fn last_chars(text: &str, n: usize) -> String {
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(n)).collect()
+}
+
+pub fn concordance(lines: &[String], file: &str, pattern: &Regex, left_width: usize, right_width: usize) -> Vec<KwicLine> {
+    // Keyword-in-context lines for every match, with up to `left_width`/`right_width` characters of context
+    let mut kwic = Vec::new();
+
+    for (i, line) in lines.iter().enumerate() {
+        for m in pattern.find_iter(line) {
+            if m.as_str().is_empty() {
+                continue;
+            }
+            kwic.push(KwicLine {
+                file: file.to_string(),
+                line: i + 1,
+                left: last_chars(&line[..m.start()], left_width),
+                keyword: m.as_str().to_string(),
+                right: line[m.end()..].chars().take(right_width).collect(),
+            });
+        }
+    }
+
+    kwic
+}
+
+pub fn sort_lines(kwic: &mut [KwicLine], by: &str) {
+    // "left" sorts on the words before the keyword, nearest first; "right" on the words after it
+    match by {
+        "left" => kwic.sort_by_cached_key(|k| {
+            let words: Vec<String> = k.left.split_whitespace().rev().map(|w| w.to_lowercase()).collect();
+            (words, k.file.clone(), k.line)
+        }),
+        "right" => kwic.sort_by_cached_key(|k| (k.right.trim_start().to_lowercase(), k.file.clone(), k.line)),
+        _ => {}
+    }
+}
+
+pub fn format_line(k: &KwicLine, left_width: usize) -> String {
+    // Plain-text KWIC line with the keyword column aligned
+    let pad = left_width.saturating_sub(k.left.chars().count());
+    format!("{}:{}\t{}{} [{}] {}", k.file, k.line, " ".repeat(pad), k.left, k.keyword, k.right)
+}
+
+#[test]
+fn test_concordance() {
+    let lines = vec![
+        "The bank raised rates.".to_string(),
+        "We sat on the river bank and a bank clerk waved.".to_string(),
+        "Banking is different.".to_string(),
+    ];
+    let pattern = query_regex("bank", false);
+
+    let mut kwic = concordance(&lines, "a.txt", &pattern, 10, 6);
+
+    assert_eq!(kwic.len(), 3);
+    assert_eq!(kwic[0].line, 1);
+    assert_eq!(kwic[0].left, "The ");
+    assert_eq!(kwic[0].right, " raise");
+    assert_eq!(kwic[1].left, "the river ");
+
+    sort_lines(&mut kwic, "left");
+    assert_eq!(kwic[0].left, "ank and a ");
+    sort_lines(&mut kwic, "right");
+    assert_eq!(kwic[0].right, " and a");
+}
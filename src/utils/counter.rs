@@ -1,35 +1,117 @@
 use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
+
+use crate::diversity::{self, LexicalDiversity};
+use crate::duplicates;
+use crate::word_freq;
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Counts {
     pub word_count: usize,
     pub line_count: usize,
     pub unique_word_count: usize,
+    pub char_count: usize,
+    pub grapheme_count: usize,
+    pub byte_count: usize,
+    pub empty_line_count: usize,
+    pub sentence_count: usize,
+    pub avg_word_length: f64,
+    pub median_word_length: f64,
+    pub avg_line_length: f64,
+    pub longest_line: usize,
+    pub diversity: LexicalDiversity,
+    pub duplicate_line_rate: f64,
+    pub stopword_ratio: f64,
+    pub mojibake_line_count: usize,
+    pub mojibake_rate: f64,
 }
 
 pub fn counter(lines: Vec<String>) -> Counts {
+    // Word and length statistics are taken from the lines as given (cleaned text)
     let mut wc = 0;
     let mut lc = 0;
+    let mut empty = 0;
+    let mut longest_line = 0;
+    let mut word_lengths: Vec<usize> = Vec::new();
     let mut unique_words = HashSet::new();
 
     for line in &lines {
-        if line != "" {
+        if !line.is_empty() {
             lc += 1;
             // Count words in the line
-            wc += line.split_whitespace().count(); 
+            let line_words = line.split_whitespace().count();
+            wc += line_words;
+            longest_line = longest_line.max(line_words);
 
-            // Count unique words
+            // Count unique words and collect word lengths in characters
             for word in line.split_whitespace() {
                 unique_words.insert(word);
+                word_lengths.push(word.chars().count());
             }
+        } else {
+            empty += 1;
         }
     }
 
+    let avg_word_length = if wc > 0 {
+        word_lengths.iter().sum::<usize>() as f64 / wc as f64
+    } else {
+        0.0
+    };
+
+    let avg_line_length = if lc > 0 { wc as f64 / lc as f64 } else { 0.0 };
+
     Counts {
         word_count: wc,
         line_count: lc,
         unique_word_count: unique_words.len(),
+        // Set by the caller from the raw lines, see text_size
+        char_count: 0,
+        grapheme_count: 0,
+        byte_count: 0,
+        empty_line_count: empty,
+        sentence_count: 0,
+        avg_word_length,
+        median_word_length: median(&mut word_lengths),
+        avg_line_length,
+        longest_line,
+        diversity: diversity::lexical_diversity(&word_freq::tokenize(&lines)),
+        // The lines are already cleaned, so they are hashed as they are
+        duplicate_line_rate: duplicates::duplicate_stats(
+            &lines.iter().map(|l| duplicates::line_key(l, true)).collect::<Vec<_>>(),
+        )
+        .rate,
+        // Set by the caller, which knows the stopword list
+        stopword_ratio: 0.0,
+        // Set by the caller, from the raw lines (cleaning removes most mojibake symbols)
+        mojibake_line_count: 0,
+        mojibake_rate: 0.0,
+    }
+}
+
+pub fn text_size(raw_lines: &[String]) -> (usize, usize, usize) {
+    // Characters, grapheme clusters and UTF-8 bytes of the decoded text before cleaning,
+    // with punctuation and combining marks; line breaks are not counted
+    let mut sizes = (0, 0, 0);
+    for line in raw_lines {
+        sizes.0 += line.chars().count();
+        sizes.1 += line.graphemes(true).count();
+        sizes.2 += line.len();
+    }
+    sizes
+}
+
+fn median(values: &mut [usize]) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) as f64 / 2.0
+    } else {
+        values[mid] as f64
     }
 }
 
@@ -47,3 +129,27 @@ fn test_counter() {
     assert_eq!(counts.line_count, 3);
     assert_eq!(counts.unique_word_count, 9);
 }
+
+#[test]
+fn test_counter_lengths() {
+    let input_lines = vec![
+        "ab abcd".to_string(),
+        "".to_string(),
+        "héllo a b".to_string(),
+    ];
+
+    let counts = counter(input_lines);
+
+    assert_eq!(counts.empty_line_count, 1);
+    assert_eq!(counts.longest_line, 3);
+    assert_eq!(counts.median_word_length, 2.0);
+    assert_eq!(counts.avg_word_length, 13.0 / 5.0);
+    assert_eq!(counts.avg_line_length, 2.5);
+}
+
+#[test]
+fn test_text_size() {
+    // "e" plus a combining acute accent is two characters but one grapheme
+    let raw_lines = vec!["Cafe\u{301}!".to_string(), "".to_string(), "héllo".to_string()];
+    assert_eq!(text_size(&raw_lines), (11, 10, 13));
+}
@@ -0,0 +1,69 @@
+use std::collections::{HashMap, HashSet};
+
+pub struct Coverage {
+    pub tokens: usize,
+    pub types: usize,
+    pub known_tokens: usize,
+    pub known_types: usize,
+    // Out-of-vocabulary words, most frequent first (ties alphabetical)
+    pub oov: Vec<(String, usize)>,
+}
+
+impl Coverage {
+    // Percentages; 0 for an empty text
+    pub fn token_coverage(&self) -> f64 {
+        percent(self.known_tokens, self.tokens)
+    }
+
+    pub fn type_coverage(&self) -> f64 {
+        percent(self.known_types, self.types)
+    }
+
+    pub fn oov_rate(&self) -> f64 {
+        percent(self.tokens - self.known_tokens, self.tokens)
+    }
+}
+
+fn percent(part: usize, total: usize) -> f64 {
+    if total == 0 { 0.0 } else { part as f64 / total as f64 * 100.0 }
+}
+
+pub fn coverage(freq: &HashMap<String, usize>, lexicon: &HashSet<String>) -> Coverage {
+    // Token and type coverage of a word frequency table by the lexicon
+    let mut oov: Vec<(String, usize)> = freq
+        .iter()
+        .filter(|(word, _)| !lexicon.contains(*word))
+        .map(|(word, &count)| (word.clone(), count))
+        .collect();
+    oov.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+    let tokens: usize = freq.values().sum();
+    let oov_tokens: usize = oov.iter().map(|(_, c)| c).sum();
+    Coverage {
+        tokens,
+        types: freq.len(),
+        known_tokens: tokens - oov_tokens,
+        known_types: freq.len() - oov.len(),
+        oov,
+    }
+}
+
+#[test]
+fn test_coverage() {
+    let lexicon: HashSet<String> = ["the", "cat", "sat"].iter().map(|w| w.to_string()).collect();
+    let mut freq = HashMap::new();
+    freq.insert("the".to_string(), 4);
+    freq.insert("cat".to_string(), 2);
+    freq.insert("zorp".to_string(), 1);
+    freq.insert("blick".to_string(), 1);
+
+    let c = coverage(&freq, &lexicon);
+    assert_eq!((c.tokens, c.types, c.known_tokens, c.known_types), (8, 4, 6, 2));
+    assert_eq!(c.token_coverage(), 75.0);
+    assert_eq!(c.type_coverage(), 50.0);
+    assert_eq!(c.oov_rate(), 25.0);
+    assert_eq!(c.oov, vec![("blick".to_string(), 1), ("zorp".to_string(), 1)]);
+
+    let empty = coverage(&HashMap::new(), &lexicon);
+    assert_eq!(empty.oov_rate(), 0.0);
+}
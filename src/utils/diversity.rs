@@ -0,0 +1,128 @@
+use std::collections::{HashMap, HashSet};
+
+// TTR threshold at which an MTLD factor is complete (McCarthy & Jarvis, 2010)
+const MTLD_THRESHOLD: f64 = 0.72;
+// Number of tokens drawn in the HD-D hypergeometric sample
+const HDD_SAMPLE: usize = 42;
+
+#[derive(Debug, Clone, Default)]
+pub struct LexicalDiversity {
+    pub ttr: f64,
+    pub root_ttr: f64,
+    pub herdan_c: f64,
+    pub yule_k: f64,
+    pub mtld: f64,
+    pub hdd: f64,
+}
+
+pub fn lexical_diversity(tokens: &[String]) -> LexicalDiversity {
+    // Length-corrected vocabulary richness measures over a token stream
+    let n = tokens.len();
+    if n == 0 {
+        return LexicalDiversity::default();
+    }
+
+    let mut freq: HashMap<&str, usize> = HashMap::new();
+    for token in tokens {
+        *freq.entry(token.as_str()).or_insert(0) += 1;
+    }
+    let v = freq.len();
+    let n_f = n as f64;
+    let v_f = v as f64;
+
+    // Herdan's C is undefined for a single token (log 1 = 0)
+    let herdan_c = if n > 1 { v_f.ln() / n_f.ln() } else { 1.0 };
+
+    // Yule's K = 10^4 * (sum of f^2 - N) / N^2
+    let sum_sq: f64 = freq.values().map(|&f| (f * f) as f64).sum();
+    let yule_k = 10_000.0 * (sum_sq - n_f) / (n_f * n_f);
+
+    LexicalDiversity {
+        ttr: v_f / n_f,
+        root_ttr: v_f / n_f.sqrt(),
+        herdan_c,
+        yule_k,
+        mtld: mtld(tokens),
+        hdd: hdd(&freq, n),
+    }
+}
+
+fn mtld_pass<'a>(tokens: impl Iterator<Item = &'a String>) -> f64 {
+    // Count how many times the running TTR drops to the threshold
+    let mut factors = 0.0;
+    let mut seen: HashSet<&str> = HashSet::new();
+    let mut segment = 0;
+    let mut total = 0;
+
+    for token in tokens {
+        total += 1;
+        segment += 1;
+        seen.insert(token.as_str());
+        let ttr = seen.len() as f64 / segment as f64;
+        if ttr <= MTLD_THRESHOLD {
+            factors += 1.0;
+            seen.clear();
+            segment = 0;
+        }
+    }
+
+    // The unfinished segment counts as a partial factor
+    if segment > 0 {
+        let ttr = seen.len() as f64 / segment as f64;
+        factors += (1.0 - ttr) / (1.0 - MTLD_THRESHOLD);
+    }
+
+    if factors == 0.0 {
+        total as f64
+    } else {
+        total as f64 / factors
+    }
+}
+
+fn mtld(tokens: &[String]) -> f64 {
+    // Average of the forward and backward passes
+    (mtld_pass(tokens.iter()) + mtld_pass(tokens.iter().rev())) / 2.0
+}
+
+fn hdd(freq: &HashMap<&str, usize>, n: usize) -> f64 {
+    // Sum over types of the probability of seeing the type in a random sample, divided by the sample size
+    let sample = HDD_SAMPLE.min(n);
+    let mut total = 0.0;
+
+    for &f in freq.values() {
+        // Probability that none of the `sample` draws hits this type
+        let mut p_none = 1.0;
+        for i in 0..sample {
+            if n - i <= f {
+                p_none = 0.0;
+                break;
+            }
+            p_none *= (n - f - i) as f64 / (n - i) as f64;
+        }
+        total += (1.0 - p_none) / sample as f64;
+    }
+
+    total
+}
+
+#[test]
+fn test_lexical_diversity() {
+    let tokens: Vec<String> = "a b a c a b d a".split(' ').map(|s| s.to_string()).collect();
+    let d = lexical_diversity(&tokens);
+
+    assert_eq!(d.ttr, 0.5);
+    assert_eq!(d.root_ttr, 4.0 / 8f64.sqrt());
+    assert!((d.herdan_c - 4f64.ln() / 8f64.ln()).abs() < 1e-12);
+    // f = 4, 2, 1, 1 -> sum of squares 22
+    assert_eq!(d.yule_k, 10_000.0 * 14.0 / 64.0);
+    // The sample covers the whole text, so every type is certainly seen
+    assert!((d.hdd - 0.5).abs() < 1e-12);
+    assert!(d.mtld > 0.0);
+}
+
+#[test]
+fn test_lexical_diversity_empty() {
+    let d = lexical_diversity(&[]);
+    assert_eq!(d.ttr, 0.0);
+    assert_eq!(d.mtld, 0.0);
+}
@@ -0,0 +1,87 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::near_dup::fnv1a;
+use crate::preprocessor;
+
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateStats {
+    // Non-empty lines, and those among them that repeat an earlier line
+    pub lines: usize,
+    pub duplicate_lines: usize,
+    pub rate: f64,
+}
+
+pub fn line_key(line: &str, raw: bool) -> Option<u64> {
+    // Hash of the cleaned line (or of the raw line with surrounding whitespace removed); None for empty lines
+    let text = if raw { line.trim().to_string() } else { preprocessor::clean_text(line) };
+    if text.is_empty() {
+        None
+    } else {
+        Some(fnv1a(text.as_bytes()))
+    }
+}
+
+pub fn keep_first_occurrences(keys: &[Option<u64>], seen: &mut HashSet<u64>) -> Vec<bool> {
+    // false for every line already in `seen`; empty lines are always kept
+    keys.iter()
+        .map(|key| match key {
+            Some(k) => seen.insert(*k),
+            None => true,
+        })
+        .collect()
+}
+
+pub fn duplicate_stats(keys: &[Option<u64>]) -> DuplicateStats {
+    let keep = keep_first_occurrences(keys, &mut HashSet::new());
+    let lines = keys.iter().filter(|k| k.is_some()).count();
+    let duplicate_lines = keep.iter().filter(|k| !**k).count();
+
+    DuplicateStats {
+        lines,
+        duplicate_lines,
+        rate: if lines == 0 { 0.0 } else { duplicate_lines as f64 / lines as f64 * 100.0 },
+    }
+}
+
+pub fn most_repeated(lines: &[&String], keys: &[Option<u64>], k: usize) -> Vec<(String, usize)> {
+    // Lines seen more than once with their number of occurrences, most repeated first
+    let mut counts: HashMap<u64, (&String, usize)> = HashMap::new();
+    for (line, key) in lines.iter().zip(keys) {
+        if let Some(key) = key {
+            counts.entry(*key).or_insert((line, 0)).1 += 1;
+        }
+    }
+
+    let mut repeated: Vec<(String, usize)> = counts
+        .into_values()
+        .filter(|(_, count)| *count > 1)
+        .map(|(line, count)| (line.trim().to_string(), count))
+        .collect();
+    repeated.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    repeated.truncate(k);
+    repeated
+}
+
+#[test]
+fn test_duplicate_lines() {
+    let lines: Vec<String> = ["Hello, world!", "hello world", "Hello world", "", "Bye", "Hello world"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+
+    let cleaned: Vec<Option<u64>> = lines.iter().map(|l| line_key(l, false)).collect();
+    let stats = duplicate_stats(&cleaned);
+    assert_eq!(stats.lines, 5);
+    assert_eq!(stats.duplicate_lines, 2);
+    assert_eq!(stats.rate, 40.0);
+
+    let raw: Vec<Option<u64>> = lines.iter().map(|l| line_key(l, true)).collect();
+    assert_eq!(duplicate_stats(&raw).duplicate_lines, 1);
+    assert_eq!(
+        keep_first_occurrences(&raw, &mut HashSet::new()),
+        vec![true, true, true, true, true, false]
+    );
+
+    let refs: Vec<&String> = lines.iter().collect();
+    assert_eq!(most_repeated(&refs, &raw, 5), vec![("Hello world".to_string(), 2)]);
+}
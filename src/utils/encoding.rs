@@ -0,0 +1,159 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252, WINDOWS_874};
+
+// Bytes looked at when guessing UTF-16 without a BOM
+const SNIFF_LEN: usize = 4096;
+
+pub struct Decoded {
+    pub lines: Vec<String>,
+    pub encoding: &'static str,
+    // Lines with bytes that could not be decoded, and how many byte sequences were replaced by U+FFFD
+    pub bad_lines: usize,
+    pub bad_sequences: usize,
+}
+
+pub fn validate_label(label: String) -> Result<(), String> {
+    // clap validator for --encoding, so an unknown label is a usage error before any input is read
+    match Encoding::for_label(label.trim().as_bytes()) {
+        Some(_) => Ok(()),
+        None => Err(format!("unknown encoding '{}' (use a WHATWG label such as utf-8, utf-16le, windows-1252 or tis-620)", label)),
+    }
+}
+
+pub fn parse_encoding(label: &str) -> &'static Encoding {
+    // WHATWG labels, e.g. "utf-8", "utf-16le", "windows-1252", "latin1", "tis-620";
+    // the command line only lets through labels accepted by validate_label
+    Encoding::for_label(label.trim().as_bytes()).unwrap_or_else(|| panic!("Unknown encoding '{}'.", label))
+}
+
+fn utf16_without_bom(bytes: &[u8]) -> Option<&'static Encoding> {
+    // Mostly-ASCII UTF-16 has a zero byte in nearly every code unit, always on the same side
+    let sample = &bytes[..bytes.len().min(SNIFF_LEN) & !1];
+    let units = sample.len() / 2;
+    if units == 0 {
+        return None;
+    }
+    let even = sample.iter().step_by(2).filter(|&&b| b == 0).count();
+    let odd = sample.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+    if odd * 10 >= units * 4 && even * 10 < units {
+        Some(UTF_16LE)
+    } else if even * 10 >= units * 4 && odd * 10 < units {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+fn utf8_validity(bytes: &[u8]) -> (usize, usize) {
+    // Valid multi-byte UTF-8 characters and invalid byte sequences
+    let (mut multibyte, mut invalid) = (0, 0);
+    let mut rest = bytes;
+    loop {
+        let (valid, error) = match std::str::from_utf8(rest) {
+            Ok(s) => (s, None),
+            Err(e) => (std::str::from_utf8(&rest[..e.valid_up_to()]).unwrap(), Some(e)),
+        };
+        multibyte += valid.chars().filter(|c| c.len_utf8() > 1).count();
+        match error {
+            None => return (multibyte, invalid),
+            Some(e) => {
+                invalid += 1;
+                // A truncated sequence at the very end has no error length
+                let skip = e.error_len().unwrap_or(rest.len() - e.valid_up_to());
+                rest = &rest[e.valid_up_to() + skip..];
+            }
+        }
+    }
+}
+
+fn looks_thai(bytes: &[u8]) -> bool {
+    // Thai in TIS-620 is written almost entirely in high bytes, so they come in long runs;
+    // accented Latin letters in Windows-1252 mostly stand alone between ASCII letters
+    let (mut runs, mut high) = (0, 0);
+    let mut in_run = false;
+    for &b in bytes {
+        if b >= 0x80 {
+            high += 1;
+            if !in_run {
+                runs += 1;
+            }
+        }
+        in_run = b >= 0x80;
+    }
+    runs > 0 && high >= runs * 3
+}
+
+pub fn detect(bytes: &[u8]) -> &'static Encoding {
+    // BOM first, then UTF-16 by its zero bytes, then UTF-8 (also when only a few sequences are broken),
+    // and otherwise one of the legacy single-byte encodings
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if let Some(encoding) = utf16_without_bom(bytes) {
+        return encoding;
+    }
+    let (multibyte, invalid) = utf8_validity(bytes);
+    if invalid == 0 || multibyte > invalid {
+        UTF_8
+    } else if looks_thai(bytes) {
+        WINDOWS_874
+    } else {
+        WINDOWS_1252
+    }
+}
+
+pub fn decode(bytes: &[u8], encoding: Option<&'static Encoding>) -> Decoded {
+    // Decodes with the given encoding (detected when None) and splits into lines; a BOM is dropped
+    let encoding = encoding.unwrap_or_else(|| detect(bytes));
+    let (text, _) = encoding.decode_with_bom_removal(bytes);
+
+    let mut bad_lines = 0;
+    let mut bad_sequences = 0;
+    let lines: Vec<String> = text
+        .lines()
+        .map(|line| {
+            let replaced = line.matches('\u{FFFD}').count();
+            if replaced > 0 {
+                bad_lines += 1;
+                bad_sequences += replaced;
+            }
+            line.to_string()
+        })
+        .collect();
+
+    Decoded {
+        lines,
+        encoding: encoding.name(),
+        bad_lines,
+        bad_sequences,
+    }
+}
+
+#[test]
+fn test_decode() {
+    // UTF-16 with and without BOM
+    let mut utf16: Vec<u8> = vec![0xFF, 0xFE];
+    for unit in "héllo\nwörld".encode_utf16() {
+        utf16.extend(unit.to_le_bytes());
+    }
+    assert_eq!(decode(&utf16, None).lines, vec!["héllo", "wörld"]);
+    assert_eq!(detect(&utf16[2..]).name(), "UTF-16LE");
+
+    // Legacy single-byte encodings
+    let latin = b"caf\xe9 cr\xe8me br\xfbl\xe9e";
+    let decoded = decode(latin, None);
+    assert_eq!((decoded.encoding, decoded.lines[0].as_str()), ("windows-1252", "café crème brûlée"));
+    let thai = b"\xca\xc7\xd1\xca\xb4\xd5 \xa4\xc3\xd1\xba";
+    let decoded = decode(thai, None);
+    assert_eq!((decoded.encoding, decoded.lines[0].as_str()), ("windows-874", "สวัสดี ครับ"));
+
+    // Mostly valid UTF-8 keeps its encoding, and the broken line is counted
+    let mut bytes = "naïve café\nbad ".as_bytes().to_vec();
+    bytes.push(0xFF);
+    let decoded = decode(&bytes, None);
+    assert_eq!((decoded.encoding, decoded.bad_lines, decoded.bad_sequences), ("UTF-8", 1, 1));
+
+    // An override wins over detection
+    assert_eq!(decode("café".as_bytes(), Some(parse_encoding("latin1"))).lines[0], "cafÃ©");
+    assert!(validate_label("Windows-1252".to_string()).is_ok());
+    assert!(validate_label("klingon".to_string()).is_err());
+}
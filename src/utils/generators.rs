@@ -3,6 +3,11 @@ use std::io::Write;
 
 use crate::Counts;
 use crate::LangInfo;
+use crate::zipf::PowerLawFit;
+use crate::spectrum::Spectrum;
+use crate::keyness::Keyness;
+use crate::concordance::KwicLine;
+use crate::cluster::Clustering;
 
 pub fn generate_html_table(count: &[Counts], file_names: &[String]) -> String {
     // HTML table generation using HTML string
@@ -34,53 +39,432 @@ pub fn generate_html_table(count: &[Counts], file_names: &[String]) -> String {
     <th style=\"text-align:center\">Counts</th>
     ");
 
-    for _i in 0..file_names.len() {
+    for file_name in file_names {
         let text = format!("
-        <th style=\"text-align:center\">{}</th>", file_names[_i]);
+        <th style=\"text-align:center\">{}</th>", file_name);
         html.push_str(&text);
     }
 
     html.push_str("</tr>");
 
+    push_row(&mut html, "Word Count", count.iter().map(|c| c.word_count.to_string()));
+    push_row(&mut html, "Unique Words", count.iter().map(|c| c.unique_word_count.to_string()));
+    push_row(&mut html, "Line Count", count.iter().map(|c| c.line_count.to_string()));
+    push_row(&mut html, "Empty Lines", count.iter().map(|c| c.empty_line_count.to_string()));
+    push_row(&mut html, "Sentence Count", count.iter().map(|c| c.sentence_count.to_string()));
+    push_row(&mut html, "Characters", count.iter().map(|c| c.char_count.to_string()));
+    push_row(&mut html, "Graphemes", count.iter().map(|c| c.grapheme_count.to_string()));
+    push_row(&mut html, "Bytes", count.iter().map(|c| c.byte_count.to_string()));
+    push_row(&mut html, "Average Word Length", count.iter().map(|c| format!("{:.2}", c.avg_word_length)));
+    push_row(&mut html, "Median Word Length", count.iter().map(|c| format!("{:.1}", c.median_word_length)));
+    push_row(&mut html, "Average Line Length (words)", count.iter().map(|c| format!("{:.2}", c.avg_line_length)));
+    push_row(&mut html, "Longest Line (words)", count.iter().map(|c| c.longest_line.to_string()));
+    push_row(&mut html, "Duplicate Line Rate", count.iter().map(|c| format!("{:.2}%", c.duplicate_line_rate)));
+    push_row(&mut html, "Stopword Ratio", count.iter().map(|c| format!("{:.2}%", c.stopword_ratio)));
+    push_row(&mut html, "Mojibake Rate", count.iter().map(|c| format!("{:.2}%", c.mojibake_rate)));
+    push_row(&mut html, "Type-Token Ratio", count.iter().map(|c| format!("{:.4}", c.diversity.ttr)));
+    push_row(&mut html, "Root TTR", count.iter().map(|c| format!("{:.4}", c.diversity.root_ttr)));
+    push_row(&mut html, "Herdan's C", count.iter().map(|c| format!("{:.4}", c.diversity.herdan_c)));
+    push_row(&mut html, "Yule's K", count.iter().map(|c| format!("{:.2}", c.diversity.yule_k)));
+    push_row(&mut html, "MTLD", count.iter().map(|c| format!("{:.2}", c.diversity.mtld)));
+    push_row(&mut html, "HD-D", count.iter().map(|c| format!("{:.4}", c.diversity.hdd)));
+
+    // table end
+    html.push_str("
+    </table>
+    ");
+
+    html
+}
+
+pub fn html_escape(text: &str) -> String {
+    // Escape raw corpus text for use inside HTML
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+pub fn json_escape(text: &str) -> String {
+    // Escape a string for use inside a JSON string literal
+    let mut escaped = String::new();
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+pub fn csv_escape(field: &str) -> String {
+    // Quote a CSV field when it contains a separator, a quote or a line break
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn counts_json(c: &Counts) -> String {
+    let d = &c.diversity;
+    format!(
+        "{{\"word_count\": {}, \"unique_word_count\": {}, \"line_count\": {}, \"empty_line_count\": {}, \
+\"sentence_count\": {}, \"char_count\": {}, \"grapheme_count\": {}, \"byte_count\": {}, \
+\"avg_word_length\": {}, \"median_word_length\": {}, \"avg_line_length\": {}, \"longest_line\": {}, \
+\"duplicate_line_rate\": {}, \"stopword_ratio\": {}, \"mojibake_line_count\": {}, \"mojibake_rate\": {}, \
+\"diversity\": {{\"ttr\": {}, \"root_ttr\": {}, \"herdan_c\": {}, \"yule_k\": {}, \"mtld\": {}, \"hdd\": {}}}}}",
+        c.word_count, c.unique_word_count, c.line_count, c.empty_line_count,
+        c.sentence_count, c.char_count, c.grapheme_count, c.byte_count,
+        c.avg_word_length, c.median_word_length, c.avg_line_length, c.longest_line,
+        c.duplicate_line_rate, c.stopword_ratio, c.mojibake_line_count, c.mojibake_rate,
+        d.ttr, d.root_ttr, d.herdan_c, d.yule_k, d.mtld, d.hdd
+    )
+}
+
+pub fn generate_json(count: &[&Counts], file_names: &[String], corpus: Option<&Counts>) -> String {
+    // JSON document with the statistics of every file and of the whole corpus
+    let mut json = String::from("{\n  \"files\": [");
+
+    for (i, (c, file_name)) in count.iter().zip(file_names).enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(&format!(
+            "\n    {{\"file\": \"{}\", \"counts\": {}}}",
+            json_escape(file_name),
+            counts_json(c)
+        ));
+    }
+    json.push_str("\n  ]");
+
+    if let Some(corpus) = corpus {
+        json.push_str(&format!(",\n  \"corpus\": {}", counts_json(corpus)));
+    }
+    json.push_str("\n}\n");
+
+    json
+}
+
+pub fn generate_spectrum_table(spectrum: &Spectrum) -> String {
+    // Table of V(m), the number of word types occurring exactly m times, followed by sample hapaxes
+    let mut html = String::new();
+
     html.push_str("
+    <table>
     <tr>
-    <td style=\"text-align:center\">Word Count</td>
+    <th style=\"text-align:center\">Occurrences</th>
+    <th style=\"text-align:center\">Word Types</th>
+    <th style=\"text-align:center\">Share of Vocabulary</th>
+    </tr>");
+
+    let label = |m: usize| match m {
+        1 => "1 (hapax legomena)".to_string(),
+        2 => "2 (dis legomena)".to_string(),
+        m => m.to_string(),
+    };
+
+    for row in &spectrum.rows {
+        html.push_str(&format!("
+    <tr>
+    <td style=\"text-align:center\">{}</td>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{:.2}%</td>
+    </tr>", label(row.frequency), row.types, row.share));
+    }
+
+    html.push_str(&format!("
+    <tr>
+    <td style=\"text-align:center\">{} or more</td>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{:.2}%</td>
+    </tr>", spectrum.rest.frequency, spectrum.rest.types, spectrum.rest.share));
+
+    html.push_str("
+    </table>
     ");
 
-    for c in count {
-        let text = format!("
-        <td style=\"text-align:right\">{}</td>", c.word_count);
-        html.push_str(&text);
+    html.push_str(&format!("<p>Sample hapaxes: {}</p>", spectrum.hapax_samples.join(", ")));
+
+    html
+}
+
+pub fn generate_keywords_table(keywords: &[(String, f64)]) -> String {
+    // Ranked keyword table with TF-IDF scores
+    let mut html = String::new();
+
+    html.push_str("
+    <table>
+    <tr>
+    <th style=\"text-align:center\">Rank</th>
+    <th style=\"text-align:center\">Keyword</th>
+    <th style=\"text-align:center\">TF-IDF</th>
+    </tr>");
+
+    for (rank, (word, score)) in keywords.iter().enumerate() {
+        html.push_str(&format!("
+    <tr>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:center\">{}</td>
+    <td style=\"text-align:right\">{:.4}</td>
+    </tr>", rank + 1, word, score));
     }
-    
-    html.push_str("</tr>");
 
     html.push_str("
+    </table>
+    ");
+
+    html
+}
+
+pub fn generate_keyness_table(scores: &[Keyness]) -> String {
+    // Keyness statistics of the strongest keywords
+    let mut html = String::new();
+
+    html.push_str("
+    <table>
+    <tr>
+    <th style=\"text-align:center\">Word</th>
+    <th style=\"text-align:center\">Target</th>
+    <th style=\"text-align:center\">Reference</th>
+    <th style=\"text-align:center\">G&sup2;</th>
+    <th style=\"text-align:center\">&chi;&sup2;</th>
+    <th style=\"text-align:center\">%DIFF</th>
+    <th style=\"text-align:center\">Log Ratio</th>
+    </tr>");
+
+    for k in scores {
+        html.push_str(&format!("
     <tr>
-    <td style=\"text-align:center\">Unique Words</td>
+    <td style=\"text-align:center\">{}</td>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{:.2}</td>
+    <td style=\"text-align:right\">{:.2}</td>
+    <td style=\"text-align:right\">{:.1}</td>
+    <td style=\"text-align:right\">{:.2}</td>
+    </tr>", k.word, k.target_freq, k.reference_freq, k.log_likelihood, k.chi_square, k.percent_diff, k.log_ratio));
+    }
+
+    html.push_str("
+    </table>
     ");
 
-    for c in count {
-        let text = format!("
-        <td style=\"text-align:right\">{}</td>", c.unique_word_count);
-        html.push_str(&text);
+    html
+}
+
+pub fn generate_keywords_json(file_names: &[String], keywords: &[Vec<(String, f64)>]) -> String {
+    let mut json = String::from("{\n  \"files\": [");
+
+    for (i, (file_name, words)) in file_names.iter().zip(keywords).enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let entries: Vec<String> = words
+            .iter()
+            .map(|(word, score)| format!("{{\"word\": \"{}\", \"score\": {}}}", json_escape(word), score))
+            .collect();
+        json.push_str(&format!(
+            "\n    {{\"file\": \"{}\", \"keywords\": [{}]}}",
+            json_escape(file_name),
+            entries.join(", ")
+        ));
     }
-    html.push_str("</tr>");
+    json.push_str("\n  ]\n}\n");
+
+    json
+}
+
+pub fn generate_topics_json(top: &[Vec<(String, f64)>], coherence: &[f64], file_names: &[String], mixtures: &[Vec<f64>]) -> String {
+    let mut json = String::from("{\n  \"topics\": [");
+
+    for (t, (words, score)) in top.iter().zip(coherence).enumerate() {
+        if t > 0 {
+            json.push(',');
+        }
+        let entries: Vec<String> = words
+            .iter()
+            .map(|(word, p)| format!("{{\"word\": \"{}\", \"probability\": {}}}", json_escape(word), p))
+            .collect();
+        json.push_str(&format!(
+            "\n    {{\"topic\": {}, \"coherence\": {}, \"words\": [{}]}}",
+            t + 1,
+            score,
+            entries.join(", ")
+        ));
+    }
+
+    json.push_str("\n  ],\n  \"documents\": [");
+    for (i, (file_name, mixture)) in file_names.iter().zip(mixtures).enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        let values: Vec<String> = mixture.iter().map(|p| p.to_string()).collect();
+        json.push_str(&format!(
+            "\n    {{\"file\": \"{}\", \"topics\": [{}]}}",
+            json_escape(file_name),
+            values.join(", ")
+        ));
+    }
+    json.push_str("\n  ]\n}\n");
+
+    json
+}
+
+pub fn generate_vocab_json(vocab: &[String]) -> String {
+    // Token to id map, one entry per line
+    let entries: Vec<String> = vocab
+        .iter()
+        .enumerate()
+        .map(|(id, token)| format!("  \"{}\": {}", json_escape(token), id))
+        .collect();
+    format!("{{\n{}\n}}\n", entries.join(",\n"))
+}
+
+pub fn generate_concordance_html(kwic: &[KwicLine], query: &str) -> String {
+    // Keyword-in-context table with the keyword highlighted
+    let mut html = String::new();
+
+    html.push_str(&format!("<h3>Concordance of \"{}\" ({} lines)</h3>", html_escape(query), kwic.len()));
 
     html.push_str("
+    <style>
+    td { padding: 2px 6px; white-space: pre; font-family: monospace; }
+    mark { background-color: yellow; }
+    </style>
+    <table>
     <tr>
-    <td style=\"text-align:center\">Line Count</td>
+    <th>File</th>
+    <th>Line</th>
+    <th style=\"text-align:right\">Left</th>
+    <th>Keyword</th>
+    <th style=\"text-align:left\">Right</th>
+    </tr>");
+
+    for k in kwic {
+        html.push_str(&format!("
+    <tr>
+    <td>{}</td>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:center\"><mark>{}</mark></td>
+    <td style=\"text-align:left\">{}</td>
+    </tr>", html_escape(&k.file), k.line, html_escape(&k.left), html_escape(&k.keyword), html_escape(&k.right)));
+    }
+
+    html.push_str("
+    </table>
     ");
 
-    for c in count {
-        let text = format!("
-        <td style=\"text-align:right\">{}</td>", c.line_count);
-        html.push_str(&text);
+    html
+}
+
+pub fn generate_cluster_html(clustering: &Clustering, members: &[Vec<String>], terms: &[Vec<String>]) -> String {
+    // Cluster sizes, top terms and member files
+    let mut html = String::new();
+
+    html.push_str(&format!(
+        "<h3>Document clusters (k = {}, silhouette score = {:.4})</h3>",
+        clustering.k, clustering.silhouette
+    ));
+
+    html.push_str("
+    <style>
+    table {
+        border: 1px solid black;
+        border-collapse: collapse;
+    }
+    th, td {
+      border: 1px solid black;
+      padding: 8px;
+    }
+    </style>
+    <table>
+    <tr>
+    <th style=\"text-align:center\">Cluster</th>
+    <th style=\"text-align:center\">Size</th>
+    <th style=\"text-align:center\">Top Terms</th>
+    <th style=\"text-align:center\">Files</th>
+    </tr>");
+
+    for (c, (files, words)) in members.iter().zip(terms).enumerate() {
+        let files: Vec<String> = files.iter().map(|f| html_escape(f)).collect();
+        html.push_str(&format!("
+    <tr>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{}</td>
+    <td>{}</td>
+    <td>{}</td>
+    </tr>", c + 1, files.len(), words.join(", "), files.join("<br>")));
     }
 
-    html.push_str("</tr>");
+    html.push_str("
+    </table>
+    ");
+
+    html
+}
+
+pub fn generate_topics_html(top: &[Vec<(String, f64)>], coherence: &[f64], file_names: &[String], mixtures: &[Vec<f64>]) -> String {
+    // Top words and coherence per topic, then the topic mixture of every file
+    let mut html = String::new();
+
+    html.push_str("
+    <style>
+    table {
+        border: 1px solid black;
+        border-collapse: collapse;
+    }
+    th, td {
+      border: 1px solid black;
+      padding: 8px;
+    }
+    </style>
+    <h3>Topics</h3>
+    <table>
+    <tr>
+    <th style=\"text-align:center\">Topic</th>
+    <th style=\"text-align:center\">Coherence (UMass)</th>
+    <th style=\"text-align:center\">Top Words</th>
+    </tr>");
+
+    for (t, (words, score)) in top.iter().zip(coherence).enumerate() {
+        let words: Vec<String> = words.iter().map(|(w, p)| format!("{} ({:.3})", html_escape(w), p)).collect();
+        html.push_str(&format!("
+    <tr>
+    <td style=\"text-align:right\">{}</td>
+    <td style=\"text-align:right\">{:.4}</td>
+    <td>{}</td>
+    </tr>", t + 1, score, words.join(", ")));
+    }
+
+    html.push_str("
+    </table>
+    <h3>Topic mixture per document</h3>
+    <table>
+    <tr>
+    <th style=\"text-align:center\">File</th>");
+    for t in 1..=top.len() {
+        html.push_str(&format!("
+    <th style=\"text-align:center\">Topic {}</th>", t));
+    }
+    html.push_str("
+    </tr>");
+
+    for (file_name, mixture) in file_names.iter().zip(mixtures) {
+        html.push_str(&format!("
+    <tr>
+    <td>{}</td>", html_escape(file_name)));
+        for p in mixture {
+            html.push_str(&format!("
+    <td style=\"text-align:right\">{:.3}</td>", p));
+        }
+        html.push_str("
+    </tr>");
+    }
 
-    // table end
     html.push_str("
     </table>
     ");
@@ -88,6 +472,22 @@ pub fn generate_html_table(count: &[Counts], file_names: &[String]) -> String {
     html
 }
 
+fn push_row(html: &mut String, label: &str, values: impl Iterator<Item = String>) {
+    // One statistic per row, one cell per file
+    html.push_str(&format!("
+    <tr>
+    <td style=\"text-align:center\">{}</td>
+    ", label));
+
+    for value in values {
+        let text = format!("
+        <td style=\"text-align:right\">{}</td>", value);
+        html.push_str(&text);
+    }
+
+    html.push_str("</tr>");
+}
+
 fn generate_svg(lang: &[String], char_vec: &[usize], percent_vec: &[f64]) -> String {
     // SVG generation for language distribution bar graph
     
@@ -161,7 +561,7 @@ fn generate_svg(lang: &[String], char_vec: &[usize], percent_vec: &[f64]) -> Str
     svg
 }
 
-pub fn generate_many_svgs(lang_infos: Vec<Vec<LangInfo>>, file_names: Vec<String>) {
+pub fn generate_many_svgs(lang_infos: &[&[LangInfo]], file_names: &[String]) {
     // Generate svg files for each text file under the folder
     
     for (lang_ls, file_name) in lang_infos.iter().zip(file_names.iter()) {
@@ -178,3 +578,140 @@ pub fn generate_many_svgs(lang_infos: Vec<Vec<LangInfo>>, file_names: Vec<String
         write!(output_file, "{}", svg).expect("Failed to write to output file");
     }
 }
+
+pub fn generate_loglog_svg(points: &[(f64, f64)], fit: &PowerLawFit, x_label: &str, y_label: &str) -> String {
+    // SVG scatter plot on log-log axes with the fitted power law as a line
+
+    let mut svg = String::new();
+
+    // Define the chart dimensions and the plotting area
+    let width = 600.0;
+    let height = 400.0;
+    let margin = 60.0;
+    let plot_w = width - 2.0 * margin;
+    let plot_h = height - 2.0 * margin;
+
+    // Axis ranges in log10 units, rounded out to whole decades
+    let positive: Vec<(f64, f64)> = points.iter().copied().filter(|(x, y)| *x > 0.0 && *y > 0.0).collect();
+    let max_x = positive.iter().map(|p| p.0).fold(1.0, f64::max).log10().ceil().max(1.0);
+    let max_y = positive.iter().map(|p| p.1).fold(1.0, f64::max).log10().ceil().max(1.0);
+
+    let to_x = |x: f64| margin + x.log10() / max_x * plot_w;
+    let to_y = |y: f64| height - margin - y.log10() / max_y * plot_h;
+
+    svg.push_str(&format!(
+        "<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        width, height
+    ));
+
+    // Axes
+    svg.push_str(&format!(
+        "<line x1=\"{m}\" y1=\"{b}\" x2=\"{r}\" y2=\"{b}\" stroke=\"black\" /><line x1=\"{m}\" y1=\"{m}\" x2=\"{m}\" y2=\"{b}\" stroke=\"black\" />",
+        m = margin, b = height - margin, r = width - margin
+    ));
+
+    // One tick per decade on each axis
+    for d in 0..=(max_x as i32) {
+        let x = to_x(10f64.powi(d));
+        svg.push_str(&format!(
+            "<text x=\"{:.1}\" y=\"{}\" text-anchor=\"middle\" font-size=\"11\">1e{}</text>",
+            x, height - margin + 16.0, d
+        ));
+    }
+    for d in 0..=(max_y as i32) {
+        let y = to_y(10f64.powi(d));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{:.1}\" text-anchor=\"end\" font-size=\"11\">1e{}</text>",
+            margin - 6.0, y + 4.0, d
+        ));
+    }
+
+    // Axis labels
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\">{}</text>",
+        margin + plot_w / 2.0, height - 15.0, x_label
+    ));
+    svg.push_str(&format!(
+        "<text x=\"15\" y=\"{}\" text-anchor=\"middle\" font-size=\"12\" transform=\"rotate(-90 15 {})\">{}</text>",
+        margin + plot_h / 2.0, margin + plot_h / 2.0, y_label
+    ));
+
+    // Data points
+    for (x, y) in &positive {
+        svg.push_str(&format!(
+            "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"2\" fill=\"steelblue\" />",
+            to_x(*x), to_y(*y)
+        ));
+    }
+
+    // Fitted line between the first and last x values
+    if let (Some(first), Some(last)) = (positive.first(), positive.last()) {
+        let line_y = |x: f64| fit.constant * x.powf(fit.exponent);
+        svg.push_str(&format!(
+            "<line x1=\"{:.1}\" y1=\"{:.1}\" x2=\"{:.1}\" y2=\"{:.1}\" stroke=\"red\" stroke-width=\"1.5\" />",
+            to_x(first.0), to_y(line_y(first.0)), to_x(last.0), to_y(line_y(last.0))
+        ));
+    }
+
+    svg.push_str("</svg>");
+
+    svg
+}
+
+pub fn generate_heatmap_svg(matrix: &[Vec<f64>], labels: &[String]) -> String {
+    // SVG heatmap of a square matrix with values in [0, 1]; darker cells are more similar
+
+    let mut svg = String::new();
+
+    // Define the cell size and the room left for labels
+    let cell = 40.0;
+    let label_space = 160.0;
+    let n = labels.len() as f64;
+    let width = label_space + n * cell + 20.0;
+    let height = label_space + n * cell + 20.0;
+
+    svg.push_str(&format!(
+        "<svg width=\"{}\" height=\"{}\" xmlns=\"http://www.w3.org/2000/svg\">",
+        width, height
+    ));
+
+    for (i, label) in labels.iter().enumerate() {
+        let offset = label_space + i as f64 * cell + cell / 2.0;
+        // Row labels on the left, column labels rotated on top
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\" text-anchor=\"end\" font-size=\"11\">{}</text>",
+            label_space - 6.0, offset + 4.0, html_escape(label)
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{x}\" y=\"{y}\" font-size=\"11\" transform=\"rotate(-60 {x} {y})\">{}</text>",
+            html_escape(label), x = offset, y = label_space - 6.0
+        ));
+    }
+
+    for (i, row) in matrix.iter().enumerate() {
+        for (j, value) in row.iter().enumerate() {
+            let v = value.clamp(0.0, 1.0);
+            // White for 0, dark blue for 1
+            let shade = |full: f64| (255.0 - v * (255.0 - full)) as u8;
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"rgb({},{},{})\" stroke=\"white\" />",
+                label_space + j as f64 * cell, label_space + i as f64 * cell, cell, cell,
+                shade(8.0), shade(48.0), shade(107.0)
+            ));
+            svg.push_str(&format!(
+                "<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" font-size=\"10\" fill=\"{}\">{:.2}</text>",
+                label_space + j as f64 * cell + cell / 2.0, label_space + i as f64 * cell + cell / 2.0 + 4.0,
+                if v > 0.5 { "white" } else { "black" }, value
+            ));
+        }
+    }
+
+    svg.push_str("</svg>");
+
+    svg
+}
+
+pub fn save_svg(file: &str, svg: &str) {
+    let mut output_file = File::create(file).expect("Failed to create output file");
+    write!(output_file, "{}", svg).expect("Failed to write to output file");
+}
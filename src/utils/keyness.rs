@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+
+// Frequency used in place of zero for %DIFF and Log Ratio, which are undefined for absent words
+const ZERO_ADJUSTMENT: f64 = 0.5;
+
+#[derive(Debug, Clone)]
+pub struct Keyness {
+    pub word: String,
+    pub target_freq: usize,
+    pub reference_freq: usize,
+    pub log_likelihood: f64,
+    pub chi_square: f64,
+    pub percent_diff: f64,
+    pub log_ratio: f64,
+}
+
+fn log_likelihood(a: f64, b: f64, c: f64, d: f64) -> f64 {
+    // G² for observed frequencies a and b in corpora of sizes c and d (Rayson & Garside, 2000)
+    let e1 = c * (a + b) / (c + d);
+    let e2 = d * (a + b) / (c + d);
+    let term = |o: f64, e: f64| if o > 0.0 { o * (o / e).ln() } else { 0.0 };
+    2.0 * (term(a, e1) + term(b, e2))
+}
+
+fn chi_square(a: f64, b: f64, c: f64, d: f64) -> f64 {
+    // Pearson's chi-square on the 2x2 table (word / other words) x (target / reference)
+    let n = c + d;
+    let cells = [
+        (a, c * (a + b) / n),
+        (b, d * (a + b) / n),
+        (c - a, c * (n - a - b) / n),
+        (d - b, d * (n - a - b) / n),
+    ];
+    cells.iter().filter(|(_, e)| *e > 0.0).map(|(o, e)| (o - e).powi(2) / e).sum()
+}
+
+pub fn keyness(target: &HashMap<String, usize>, reference: &HashMap<String, usize>) -> Vec<Keyness> {
+    // Keyness statistics for every word in either frequency table
+    let c: f64 = target.values().sum::<usize>() as f64;
+    let d: f64 = reference.values().sum::<usize>() as f64;
+    if c == 0.0 || d == 0.0 {
+        return Vec::new();
+    }
+
+    let words: HashSet<&String> = target.keys().chain(reference.keys()).collect();
+
+    words
+        .into_iter()
+        .map(|word| {
+            let target_freq = target.get(word).copied().unwrap_or(0);
+            let reference_freq = reference.get(word).copied().unwrap_or(0);
+            let a = target_freq as f64;
+            let b = reference_freq as f64;
+
+            // Normalised frequencies per million words, with zero frequencies adjusted
+            let norm_a = a.max(ZERO_ADJUSTMENT) / c * 1_000_000.0;
+            let norm_b = b.max(ZERO_ADJUSTMENT) / d * 1_000_000.0;
+
+            Keyness {
+                word: word.clone(),
+                target_freq,
+                reference_freq,
+                log_likelihood: log_likelihood(a, b, c, d),
+                chi_square: chi_square(a, b, c, d),
+                percent_diff: (norm_a - norm_b) * 100.0 / norm_b,
+                log_ratio: (norm_a / norm_b).log2(),
+            }
+        })
+        .collect()
+}
+
+pub fn split_by_direction(mut scores: Vec<Keyness>) -> (Vec<Keyness>, Vec<Keyness>) {
+    // Positive (overused in the target) and negative (underused) keywords, each sorted by
+    // log-likelihood, strongest first; words with the same relative frequency are in neither list
+    scores.sort_by(|x, y| y.log_likelihood.total_cmp(&x.log_likelihood).then_with(|| x.word.cmp(&y.word)));
+    let negative = scores.iter().filter(|k| k.log_ratio < 0.0).cloned().collect();
+    scores.retain(|k| k.log_ratio > 0.0);
+    (scores, negative)
+}
+
+#[test]
+fn test_keyness() {
+    let mut target = HashMap::new();
+    target.insert("news".to_string(), 10);
+    target.insert("the".to_string(), 90);
+    let mut reference = HashMap::new();
+    reference.insert("lol".to_string(), 10);
+    reference.insert("the".to_string(), 90);
+
+    let (positive, negative) = split_by_direction(keyness(&target, &reference));
+
+    assert_eq!(positive[0].word, "news");
+    assert_eq!(negative[0].word, "lol");
+    // G² for 10 vs 0 in corpora of 100 words each = 2 * 10 * ln 2
+    assert!((positive[0].log_likelihood - 20.0 * 2f64.ln()).abs() < 1e-9);
+    // Equal relative frequency means no difference at all
+    assert_eq!(positive.len() + negative.len(), 2);
+    let the = keyness(&target, &reference).into_iter().find(|k| k.word == "the").unwrap();
+    assert_eq!(the.log_ratio, 0.0);
+    assert_eq!(the.percent_diff, 0.0);
+    assert_eq!(the.log_likelihood, 0.0);
+    assert!(the.chi_square.abs() < 1e-12);
+}
@@ -0,0 +1,53 @@
+use std::collections::HashMap;
+
+pub fn tf_idf(docs: &[&HashMap<String, usize>]) -> Vec<HashMap<String, f64>> {
+    // TF-IDF weight of every word in every document
+    // tf = count / document length, idf = ln((1 + N) / (1 + df)) + 1 (smoothed, never zero)
+    let n = docs.len() as f64;
+
+    let mut document_frequency: HashMap<&str, usize> = HashMap::new();
+    for doc in docs {
+        for word in doc.keys() {
+            *document_frequency.entry(word.as_str()).or_insert(0) += 1;
+        }
+    }
+
+    docs.iter()
+        .map(|doc| {
+            let length: usize = doc.values().sum();
+            doc.iter()
+                .map(|(word, &count)| {
+                    let tf = count as f64 / length as f64;
+                    let df = document_frequency[word.as_str()] as f64;
+                    let idf = ((1.0 + n) / (1.0 + df)).ln() + 1.0;
+                    (word.clone(), tf * idf)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+pub fn top_keywords(scores: &HashMap<String, f64>, k: usize) -> Vec<(String, f64)> {
+    // Highest scoring words first; ties are broken alphabetically so the output is stable
+    let mut ranked: Vec<(String, f64)> = scores.iter().map(|(w, &s)| (w.clone(), s)).collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(k);
+    ranked
+}
+
+#[test]
+fn test_tf_idf() {
+    let mut doc1 = HashMap::new();
+    doc1.insert("the".to_string(), 2);
+    doc1.insert("cat".to_string(), 2);
+    let mut doc2 = HashMap::new();
+    doc2.insert("the".to_string(), 2);
+    doc2.insert("dog".to_string(), 2);
+
+    let scores = tf_idf(&[&doc1, &doc2]);
+
+    // "the" is in both documents so it gets the minimum idf of 1
+    assert_eq!(scores[0]["the"], 0.5);
+    assert!(scores[0]["cat"] > scores[0]["the"]);
+    assert_eq!(top_keywords(&scores[1], 1)[0].0, "dog");
+}
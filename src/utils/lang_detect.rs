@@ -33,9 +33,9 @@ pub struct LangInfo {
 pub fn find_unicode(langtype: [LangType; 59], c: char) -> Lang {
     let uni = c as u64;
 
-    for i in 0..58 {
-        if uni <= langtype[i].uniup && uni >= langtype[i].unidown {
-            return langtype[i].lang;
+    for lt in langtype.iter().take(58) {
+        if uni <= lt.uniup && uni >= lt.unidown {
+            return lt.lang;
         }
     }
     
@@ -106,11 +106,9 @@ pub fn return_lang(lang: Lang) -> String {
     }    
 }
 
-pub fn lang_detect(lines: Vec<String>) -> Vec<LangInfo> {
-    // Language detection using unicode character ranges
-    
+fn lang_types() -> [LangType; 59] {
     // make an array of struct for each language
-    let langtype = [
+    [
         LangType{lang: Lang::English, unidown: 0x0041, uniup: 0x007A, text: "English".to_string()},
         LangType{lang: Lang::Spanish, unidown: 0x00C0, uniup: 0x00FF, text: "Spanish".to_string()},
         LangType{lang: Lang::French, unidown: 0x00C0, uniup: 0x017F, text: "French".to_string()},
@@ -170,7 +168,33 @@ pub fn lang_detect(lines: Vec<String>) -> Vec<LangInfo> {
         LangType{lang: Lang::Malayalam, unidown: 0x0D00, uniup: 0x0D7F, text: "Malayalam".to_string()},
         LangType{lang: Lang::Ascii, unidown: 0x0020, uniup: 0x007E, text: "ASCII".to_string()},
         LangType{lang: Lang::Unknown, unidown: 0x0000, uniup: 0x0000, text: "Unknown".to_string()},
-    ];
+    ]
+}
+
+pub fn word_lang(word: &str) -> String {
+    // Language of the majority of a word's characters (ties go to the language seen first)
+    let langtype = lang_types();
+    let mut counts: Vec<(Lang, usize)> = Vec::new();
+    for c in word.chars() {
+        let lang = find_unicode(langtype.clone(), c);
+        match counts.iter_mut().find(|(l, _)| *l == lang) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((lang, 1)),
+        }
+    }
+
+    let mut best = (Lang::Unknown, 0);
+    for (lang, count) in counts {
+        if count > best.1 {
+            best = (lang, count);
+        }
+    }
+    return_lang(best.0)
+}
+
+pub fn lang_detect(lines: Vec<String>) -> Vec<LangInfo> {
+    // Language detection using unicode character ranges
+    let langtype = lang_types();
 
     let mut total_words = 0;
     let mut language_counts: HashMap<Lang, HashMap<Lang, usize>> = HashMap::new();
@@ -191,7 +215,7 @@ pub fn lang_detect(lines: Vec<String>) -> Vec<LangInfo> {
             }
 
             for (lang, count) in word_language_counts.iter() {
-                let language_count = language_counts.entry(*lang).or_insert(HashMap::new());
+                let language_count = language_counts.entry(*lang).or_default();
                 let word_count = language_count.entry(*lang).or_insert(0);
                 *word_count += count;
             }
@@ -0,0 +1,236 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::word_freq;
+
+pub const BOS: &str = "<s>";
+pub const EOS: &str = "</s>";
+pub const UNK: &str = "<unk>";
+
+pub trait LanguageModel {
+    fn order(&self) -> usize;
+    // log10 P(word | context); context holds at most order - 1 words, most recent last
+    fn log10_prob(&self, context: &[String], word: &str) -> f64;
+    fn contains(&self, word: &str) -> bool;
+}
+
+#[derive(Debug, Clone)]
+pub struct LineScore {
+    // Predicted tokens (words and </s>), how many were out of vocabulary, total log10 probability
+    pub tokens: usize,
+    pub oov: usize,
+    pub log10_prob: f64,
+    pub perplexity: f64,
+}
+
+pub fn line_tokens(line: &str) -> Vec<String> {
+    // <s> w1 ... wn </s>, with words as in word_freq::tokenize
+    let mut tokens = vec![BOS.to_string()];
+    tokens.extend(word_freq::tokenize(&[line.to_string()]));
+    tokens.push(EOS.to_string());
+    tokens
+}
+
+pub fn score_line(model: &dyn LanguageModel, line: &str) -> LineScore {
+    let tokens: Vec<String> = line_tokens(line)
+        .into_iter()
+        .map(|t| if t == BOS || model.contains(&t) { t } else { UNK.to_string() })
+        .collect();
+
+    let mut log10_prob = 0.0;
+    for i in 1..tokens.len() {
+        let start = i.saturating_sub(model.order() - 1);
+        log10_prob += model.log10_prob(&tokens[start..i], &tokens[i]);
+    }
+    let predicted = tokens.len() - 1;
+
+    LineScore {
+        tokens: predicted,
+        oov: tokens.iter().filter(|t| *t == UNK).count(),
+        log10_prob,
+        perplexity: 10f64.powf(-log10_prob / predicted as f64),
+    }
+}
+
+pub fn perplexity(scores: &[LineScore]) -> f64 {
+    // Perplexity over all predicted tokens together
+    let tokens: usize = scores.iter().map(|s| s.tokens).sum();
+    let log10_prob = scores.iter().map(|s| s.log10_prob).fold(0.0, |acc, v| acc + v);
+    if tokens == 0 { 0.0 } else { 10f64.powf(-log10_prob / tokens as f64) }
+}
+
+#[derive(Debug, Clone, Default)]
+struct ContextStats {
+    // Sum of counts of the n-grams continuing a context, and how many of them have count 1, 2 and 3+
+    total: usize,
+    types: [usize; 3],
+}
+
+pub struct KneserNey {
+    order: usize,
+    // Index k - 1 holds k-grams: raw counts for the highest order and for n-grams starting with <s>,
+    // continuation counts (number of distinct preceding words) otherwise
+    counts: Vec<HashMap<Vec<String>, usize>>,
+    contexts: Vec<HashMap<Vec<String>, ContextStats>>,
+    // D1, D2, D3+ of every order
+    discounts: Vec<[f64; 3]>,
+    vocab: HashSet<String>,
+}
+
+fn discounts(counts: &HashMap<Vec<String>, usize>) -> [f64; 3] {
+    // Chen & Goodman's estimates from the count-of-counts n1..n4, kept inside (0, c) for tiny corpora
+    let mut n = [0usize; 4];
+    for &c in counts.values() {
+        if (1..=4).contains(&c) {
+            n[c - 1] += 1;
+        }
+    }
+    let fallback = [0.5, 1.0, 1.5];
+    if n.contains(&0) {
+        return fallback;
+    }
+
+    let y = n[0] as f64 / (n[0] as f64 + 2.0 * n[1] as f64);
+    let mut d = [0.0; 3];
+    for (i, value) in d.iter_mut().enumerate() {
+        let c = (i + 1) as f64;
+        let estimate = c - (c + 1.0) * y * n[i + 1] as f64 / n[i] as f64;
+        *value = if estimate > 0.0 && estimate < c { estimate } else { fallback[i] };
+    }
+    d
+}
+
+impl KneserNey {
+    pub fn train(lines: &[String], order: usize) -> KneserNey {
+        let order = order.max(1);
+        let mut raw: Vec<HashMap<Vec<String>, usize>> = vec![HashMap::new(); order];
+        let mut preceding: Vec<HashMap<Vec<String>, HashSet<String>>> = vec![HashMap::new(); order];
+        let mut vocab: HashSet<String> = HashSet::new();
+
+        for line in lines {
+            let tokens = line_tokens(line);
+            if tokens.len() == 2 {
+                continue;
+            }
+            vocab.extend(tokens[1..].iter().cloned());
+            // Every k-gram ending at a predicted position i >= 1
+            for i in 1..tokens.len() {
+                for k in 1..=order.min(i + 1) {
+                    let gram = tokens[i + 1 - k..=i].to_vec();
+                    if k < order && i + 1 > k {
+                        preceding[k - 1].entry(gram.clone()).or_default().insert(tokens[i - k].clone());
+                    }
+                    *raw[k - 1].entry(gram).or_insert(0) += 1;
+                }
+            }
+        }
+        vocab.insert(UNK.to_string());
+
+        let mut counts = Vec::with_capacity(order);
+        for k in 1..=order {
+            let adjusted: HashMap<Vec<String>, usize> = raw[k - 1]
+                .iter()
+                .map(|(gram, &c)| {
+                    let count = if k == order || gram[0] == BOS { c } else { preceding[k - 1][gram].len() };
+                    (gram.clone(), count)
+                })
+                .collect();
+            counts.push(adjusted);
+        }
+
+        let discounts: Vec<[f64; 3]> = counts.iter().map(discounts).collect();
+
+        let mut contexts: Vec<HashMap<Vec<String>, ContextStats>> = vec![HashMap::new(); order];
+        for (k, grams) in counts.iter().enumerate() {
+            for (gram, &c) in grams {
+                let stats = contexts[k].entry(gram[..gram.len() - 1].to_vec()).or_default();
+                stats.total += c;
+                stats.types[c.min(3) - 1] += 1;
+            }
+        }
+
+        KneserNey { order, counts, contexts, discounts, vocab }
+    }
+
+    fn discount(&self, k: usize, count: usize) -> f64 {
+        if count == 0 { 0.0 } else { self.discounts[k - 1][count.min(3) - 1] }
+    }
+
+    pub fn backoff(&self, context: &[String]) -> Option<f64> {
+        // Interpolation weight γ(h) = (D1 N1(h•) + D2 N2(h•) + D3+ N3+(h•)) / c(h), for seen contexts
+        let k = context.len() + 1;
+        self.contexts.get(k - 1)?.get(context).map(|stats| {
+            let d = &self.discounts[k - 1];
+            (d[0] * stats.types[0] as f64 + d[1] * stats.types[1] as f64 + d[2] * stats.types[2] as f64)
+                / stats.total as f64
+        })
+    }
+
+    pub fn prob(&self, context: &[String], word: &str) -> f64 {
+        // Interpolated modified Kneser-Ney:
+        // P(w | h) = max(c(hw) - D(c(hw)), 0) / c(h) + γ(h) P(w | h'), ending in the uniform distribution
+        let lower = if context.is_empty() {
+            1.0 / self.vocab.len() as f64
+        } else {
+            self.prob(&context[1..], word)
+        };
+
+        let k = context.len() + 1;
+        match self.contexts[k - 1].get(context) {
+            None => lower,
+            Some(stats) => {
+                let mut gram = context.to_vec();
+                gram.push(word.to_string());
+                let c = self.counts[k - 1].get(&gram).copied().unwrap_or(0);
+                let discounted = (c as f64 - self.discount(k, c)).max(0.0) / stats.total as f64;
+                discounted + self.backoff(context).unwrap_or(0.0) * lower
+            }
+        }
+    }
+
+    pub fn ngrams(&self, k: usize) -> Vec<&Vec<String>> {
+        // The k-grams seen in training, sorted
+        let mut grams: Vec<&Vec<String>> = self.counts[k - 1].keys().collect();
+        grams.sort();
+        grams
+    }
+}
+
+impl LanguageModel for KneserNey {
+    fn order(&self) -> usize {
+        self.order
+    }
+
+    fn log10_prob(&self, context: &[String], word: &str) -> f64 {
+        let context = &context[context.len().saturating_sub(self.order - 1)..];
+        self.prob(context, word).log10()
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.vocab.contains(word)
+    }
+}
+
+#[test]
+fn test_kneser_ney() {
+    let lines: Vec<String> = ["the cat sat on the mat", "the dog sat on the log", "a cat and a dog"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let model = KneserNey::train(&lines, 3);
+
+    // Every distribution sums to one over the vocabulary
+    let mut vocab: Vec<&String> = model.vocab.iter().collect();
+    vocab.sort();
+    for context in [vec![], vec!["the".to_string()], vec!["sat".to_string(), "on".to_string()], vec!["zebra".to_string()]] {
+        let total: f64 = vocab.iter().map(|w| model.prob(&context, w)).sum();
+        assert!((total - 1.0).abs() < 1e-9, "{:?} sums to {}", context, total);
+    }
+
+    let seen = score_line(&model, "the cat sat on the log");
+    let noise = score_line(&model, "log the on a sat mat");
+    assert_eq!(seen.tokens, 7);
+    assert_eq!(seen.oov, 0);
+    assert!(seen.perplexity < noise.perplexity);
+    assert_eq!(score_line(&model, "the zebra").oov, 1);
+    assert!(perplexity(&[seen.clone(), noise.clone()]) > seen.perplexity);
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+
+use crate::lm::{BOS, EOS};
+use crate::ngram;
+use crate::rng::Rng;
+
+// Generated sentences are cut off after this many words if </s> has not been drawn
+pub const MAX_WORDS: usize = 50;
+
+pub struct MarkovChain {
+    order: usize,
+    // Context of 0 to order - 1 tokens -> counts of the tokens that follow it
+    next: HashMap<Vec<String>, Vec<(String, usize)>>,
+}
+
+impl MarkovChain {
+    pub fn train(sentences: &[Vec<String>], order: usize) -> MarkovChain {
+        // Sentences are padded with order - 1 <s> tokens and one </s>
+        let order = order.max(1);
+        let padded: Vec<Vec<String>> = sentences
+            .iter()
+            .filter(|words| !words.is_empty())
+            .map(|words| {
+                let mut tokens = vec![BOS.to_string(); order - 1];
+                tokens.extend(words.iter().cloned());
+                tokens.push(EOS.to_string());
+                tokens
+            })
+            .collect();
+
+        let mut next: HashMap<Vec<String>, Vec<(String, usize)>> = HashMap::new();
+        for n in 1..=order {
+            for (gram, count) in ngram::ngram_counts(&padded, n) {
+                let (context, word) = gram.split_at(n - 1);
+                if word[0] != BOS {
+                    next.entry(context.to_vec()).or_default().push((word[0].clone(), count));
+                }
+            }
+        }
+        // Fixed order, so that a seed always gives the same text
+        for continuations in next.values_mut() {
+            continuations.sort();
+        }
+
+        MarkovChain { order, next }
+    }
+
+    fn sample_next(&self, tokens: &[String], temperature: f64, rng: &mut Rng) -> String {
+        // Longest seen context wins; weights are count^(1 / temperature), and temperature 0 is greedy
+        // (ties go to the first word)
+        let start = tokens.len().saturating_sub(self.order - 1);
+        let continuations = (start..=tokens.len())
+            .find_map(|s| self.next.get(&tokens[s..]))
+            .expect("The corpus has no words to generate text from.");
+
+        if temperature <= 0.0 {
+            let best = continuations.iter().max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0))).unwrap();
+            return best.0.clone();
+        }
+
+        // Scaled by the largest count first, so that low temperatures cannot overflow to infinity
+        let max_count = continuations.iter().map(|(_, c)| *c).max().unwrap() as f64;
+        let weights: Vec<f64> = continuations
+            .iter()
+            .map(|(_, c)| (*c as f64 / max_count).powf(1.0 / temperature))
+            .collect();
+        let total: f64 = weights.iter().sum();
+        let mut target = rng.next_f64() * total;
+        for ((word, _), w) in continuations.iter().zip(&weights) {
+            if target < *w {
+                return word.clone();
+            }
+            target -= w;
+        }
+        continuations[continuations.len() - 1].0.clone()
+    }
+
+    pub fn generate(&self, prompt: &[String], temperature: f64, rng: &mut Rng) -> Vec<String> {
+        // Words of one sentence, starting with the prompt
+        let mut tokens = vec![BOS.to_string(); self.order - 1];
+        tokens.extend(prompt.iter().cloned());
+
+        while tokens.len() - (self.order - 1) < MAX_WORDS {
+            let word = self.sample_next(&tokens, temperature, rng);
+            if word == EOS {
+                break;
+            }
+            tokens.push(word);
+        }
+        tokens.split_off(self.order - 1)
+    }
+}
+
+#[test]
+fn test_markov_generation() {
+    let sentences: Vec<Vec<String>> = ["the cat sat on the mat", "the cat ate the fish", "a dog sat on the rug"]
+        .iter()
+        .map(|s| s.split(' ').map(|w| w.to_string()).collect())
+        .collect();
+    let chain = MarkovChain::train(&sentences, 3);
+
+    // Same seed, same text
+    let first = chain.generate(&[], 1.0, &mut Rng::new(7));
+    assert_eq!(first, chain.generate(&[], 1.0, &mut Rng::new(7)));
+    assert!(!first.is_empty());
+
+    // Greedy decoding follows the most frequent continuation (ties go to the first word)
+    let prompt = vec!["a".to_string()];
+    assert_eq!(chain.generate(&prompt, 0.0, &mut Rng::new(1)), vec!["a", "dog", "sat", "on", "the", "mat"]);
+
+    // A very low temperature still picks the most frequent continuation
+    let skewed: Vec<Vec<String>> = (0..12)
+        .map(|i| vec!["alpha".to_string(), if i < 10 { "beta" } else { "gamma" }.to_string()])
+        .collect();
+    let skewed_chain = MarkovChain::train(&skewed, 2);
+    for seed in 0..20 {
+        assert_eq!(skewed_chain.generate(&["alpha".to_string()], 0.001, &mut Rng::new(seed)), vec!["alpha", "beta"]);
+    }
+
+    // An unseen prompt word backs off to shorter contexts
+    let unseen = chain.generate(&["zebra".to_string()], 1.0, &mut Rng::new(3));
+    assert_eq!(unseen[0], "zebra");
+}
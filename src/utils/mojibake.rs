@@ -0,0 +1,120 @@
+use encoding_rs::WINDOWS_1252;
+
+// Lines scoring at least this much are counted as mojibake
+pub const MIN_SCORE: f64 = 0.5;
+
+// Text encoded twice or three times is repaired one round at a time
+const MAX_ROUNDS: usize = 3;
+
+fn to_byte(c: char) -> Option<u8> {
+    // The byte that Latin-1 or Windows-1252 decoded as this character, for non-ASCII characters only
+    let code = c as u32;
+    if (0xA0..=0xFF).contains(&code) {
+        return Some(code as u8);
+    }
+    let mut buf = [0; 4];
+    let (bytes, _, had_errors) = WINDOWS_1252.encode(c.encode_utf8(&mut buf));
+    if !had_errors && bytes.len() == 1 && bytes[0] >= 0x80 {
+        Some(bytes[0])
+    } else if (0x80..=0x9F).contains(&code) {
+        Some(code as u8)
+    } else {
+        None
+    }
+}
+
+fn utf8_char(bytes: &[u8]) -> Option<(char, usize)> {
+    // A multi-byte UTF-8 character at the start of the bytes, and its length
+    let len = match bytes.first()? {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    let s = std::str::from_utf8(bytes.get(..len)?).ok()?;
+    s.chars().next().map(|c| (c, len))
+}
+
+fn repair_round(line: &str) -> (String, usize) {
+    // Replaces every character run that reads as UTF-8 once mapped back to Windows-1252 bytes;
+    // also returns how many characters were replaced
+    let chars: Vec<char> = line.chars().collect();
+    let mut repaired = String::with_capacity(line.len());
+    let mut replaced = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let bytes: Vec<u8> = chars[i..].iter().take(4).map_while(|&c| to_byte(c)).collect();
+        match utf8_char(&bytes) {
+            Some((c, len)) => {
+                repaired.push(c);
+                replaced += len;
+                i += len;
+            }
+            None => {
+                repaired.push(chars[i]);
+                i += 1;
+            }
+        }
+    }
+    (repaired, replaced)
+}
+
+pub fn repair(line: &str) -> String {
+    // Reverses UTF-8 text that was decoded as Latin-1 or Windows-1252, e.g. "cÃ³mo" -> "cómo"
+    let mut line = line.to_string();
+    for _ in 0..MAX_ROUNDS {
+        let (repaired, replaced) = repair_round(&line);
+        if replaced == 0 {
+            break;
+        }
+        line = repaired;
+    }
+    line
+}
+
+pub fn score(line: &str) -> f64 {
+    // Share of the non-ASCII characters that are part of a repairable sequence (0 = clean, 1 = all broken)
+    let non_ascii = line.chars().filter(|c| !c.is_ascii()).count();
+    if non_ascii == 0 {
+        return 0.0;
+    }
+    let (_, replaced) = repair_round(line);
+    replaced as f64 / non_ascii as f64
+}
+
+pub fn is_flagged(raw_line: &str, line: &str) -> bool {
+    // A raw line that scores at least MIN_SCORE, counted only when its cleaned line is one of
+    // the non-empty lines of counter::Counts::line_count
+    !line.is_empty() && score(raw_line) >= MIN_SCORE
+}
+
+pub fn flagged_count(raw_lines: &[String], lines: &[String]) -> usize {
+    raw_lines.iter().zip(lines).filter(|(raw, line)| is_flagged(raw, line)).count()
+}
+
+pub fn mojibake_rate(flagged: usize, line_count: usize) -> f64 {
+    // Percentage of the non-empty lines that are flagged; corpus rates are taken from summed counts
+    if line_count == 0 { 0.0 } else { flagged as f64 / line_count as f64 * 100.0 }
+}
+
+#[test]
+fn test_mojibake() {
+    assert_eq!(repair("Â¿CÃ³mo estÃ¡s?"), "¿Cómo estás?");
+    assert_eq!(repair("â€œquotedâ€\u{9d} â€” done"), "\u{201c}quoted\u{201d} \u{2014} done");
+    // Encoded twice
+    assert_eq!(repair("cÃƒÂ³mo"), "cómo");
+    // Correct text is left alone, also next to a broken word
+    assert_eq!(repair("café crème"), "café crème");
+    assert_eq!(repair("très cafÃ©"), "très café");
+
+    assert_eq!(score("cÃ³mo"), 1.0);
+    assert_eq!(score("cómo"), 0.0);
+    assert_eq!(score("plain ascii"), 0.0);
+    // Empty lines are not part of the denominator
+    let raw_lines = vec!["cÃ³mo".to_string(), "cómo".to_string(), "".to_string(), "".to_string()];
+    let lines = vec!["cmo".to_string(), "cómo".to_string(), "".to_string(), "".to_string()];
+    assert_eq!(flagged_count(&raw_lines, &lines), 1);
+    assert_eq!(mojibake_rate(1, 2), 50.0);
+    assert_eq!(mojibake_rate(0, 0), 0.0);
+}
@@ -0,0 +1,207 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::rng::Rng;
+
+// Number of hash functions in a MinHash signature
+pub const NUM_HASHES: usize = 128;
+// Shingles are overlapping character 5-grams, which also work for scripts written without spaces
+const SHINGLE_SIZE: usize = 5;
+const MERSENNE_PRIME: u64 = (1 << 61) - 1;
+
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    // 64-bit FNV-1a, a stable hash that does not change between runs or Rust versions
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    hash
+}
+
+fn shingles(text: &str) -> HashSet<u64> {
+    let chars: Vec<char> = text.to_lowercase().chars().collect();
+    let mut set = HashSet::new();
+
+    if chars.len() <= SHINGLE_SIZE {
+        if !chars.is_empty() {
+            set.insert(fnv1a(text.to_lowercase().as_bytes()));
+        }
+        return set;
+    }
+    for window in chars.windows(SHINGLE_SIZE) {
+        let shingle: String = window.iter().collect();
+        set.insert(fnv1a(shingle.as_bytes()));
+    }
+    set
+}
+
+pub struct MinHasher {
+    // (a, b) of each hash function h(x) = (a * x + b) mod p
+    params: Vec<(u64, u64)>,
+}
+
+impl MinHasher {
+    pub fn new(seed: u64) -> MinHasher {
+        let mut rng = Rng::new(seed);
+        let params = (0..NUM_HASHES)
+            .map(|_| (rng.next_u64() % (MERSENNE_PRIME - 1) + 1, rng.next_u64() % MERSENNE_PRIME))
+            .collect();
+        MinHasher { params }
+    }
+
+    pub fn signature(&self, text: &str) -> Vec<u64> {
+        // Minimum of every hash function over the shingle set; empty for an empty text
+        let shingles = shingles(text);
+        if shingles.is_empty() {
+            return Vec::new();
+        }
+        self.params
+            .iter()
+            .map(|&(a, b)| {
+                shingles
+                    .iter()
+                    .map(|&x| ((a as u128 * (x % MERSENNE_PRIME) as u128 + b as u128) % MERSENNE_PRIME as u128) as u64)
+                    .min()
+                    .unwrap()
+            })
+            .collect()
+    }
+}
+
+pub fn similarity(a: &[u64], b: &[u64]) -> f64 {
+    // Estimated Jaccard similarity: the share of matching signature positions
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    a.iter().zip(b).filter(|(x, y)| x == y).count() as f64 / a.len() as f64
+}
+
+fn lsh_params(threshold: f64) -> (usize, usize) {
+    // Pick the most rows per band whose S-curve midpoint (1/b)^(1/r) is still below the threshold,
+    // so that pairs at the threshold are very likely to share a bucket
+    let mut best = (NUM_HASHES, 1);
+    for rows in 1..=NUM_HASHES {
+        if !NUM_HASHES.is_multiple_of(rows) {
+            continue;
+        }
+        let bands = NUM_HASHES / rows;
+        if (1.0 / bands as f64).powf(1.0 / rows as f64) <= threshold {
+            best = (bands, rows);
+        }
+    }
+    best
+}
+
+fn find(parent: &mut [usize], i: usize) -> usize {
+    let mut root = i;
+    while parent[root] != root {
+        root = parent[root];
+    }
+    // Path compression
+    let mut node = i;
+    while parent[node] != root {
+        let next = parent[node];
+        parent[node] = root;
+        node = next;
+    }
+    root
+}
+
+pub fn near_duplicate_clusters(signatures: &[Vec<u64>], threshold: f64) -> Vec<Vec<usize>> {
+    // Groups of items whose estimated similarity to another member reaches the threshold.
+    // Candidates come from LSH banding; every member of a bucket is then checked on the full signature
+    // against the bucket's first member only, so large buckets (boilerplate lines) stay linear
+    let (bands, rows) = lsh_params(threshold);
+    let mut parent: Vec<usize> = (0..signatures.len()).collect();
+
+    for band in 0..bands {
+        let mut buckets: HashMap<&[u64], Vec<usize>> = HashMap::new();
+        for (i, sig) in signatures.iter().enumerate() {
+            if !sig.is_empty() {
+                buckets.entry(&sig[band * rows..(band + 1) * rows]).or_default().push(i);
+            }
+        }
+        for members in buckets.values().filter(|m| m.len() > 1) {
+            let i = members[0];
+            for &j in &members[1..] {
+                if find(&mut parent, i) != find(&mut parent, j)
+                    && similarity(&signatures[i], &signatures[j]) >= threshold
+                {
+                    let (ri, rj) = (find(&mut parent, i), find(&mut parent, j));
+                    parent[ri.max(rj)] = ri.min(rj);
+                }
+            }
+        }
+    }
+
+    let mut clusters: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..signatures.len() {
+        let root = find(&mut parent, i);
+        clusters.entry(root).or_default().push(i);
+    }
+
+    // Only real groups, ordered by their first member
+    let mut clusters: Vec<Vec<usize>> = clusters.into_values().filter(|c| c.len() > 1).collect();
+    clusters.sort();
+    clusters
+}
+
+pub fn line_clusters(signatures: &[Vec<Vec<u64>>], keep_file: &[bool], threshold: f64) -> Vec<Vec<(usize, usize)>> {
+    // Near-duplicate lines as (file, line) positions, over the files that are kept only: lines of
+    // dropped files are neither kept as the first member of a cluster nor counted as redundant
+    let positions: Vec<(usize, usize)> = signatures
+        .iter()
+        .enumerate()
+        .filter(|&(f, _)| keep_file[f])
+        .flat_map(|(f, lines)| (0..lines.len()).map(move |l| (f, l)))
+        .collect();
+    let flat: Vec<Vec<u64>> = positions.iter().map(|&(f, l)| signatures[f][l].clone()).collect();
+    near_duplicate_clusters(&flat, threshold)
+        .iter()
+        .map(|cluster| cluster.iter().map(|&i| positions[i]).collect())
+        .collect()
+}
+
+#[test]
+fn test_line_clusters() {
+    let boilerplate = "Subscribe to our newsletter for the latest corpus news";
+    let files = [
+        vec![boilerplate, "first file text"],
+        vec!["a dropped near-duplicate file", boilerplate],
+        vec![boilerplate],
+    ];
+    let hasher = MinHasher::new(7);
+    let signatures: Vec<Vec<Vec<u64>>> = files
+        .iter()
+        .map(|lines| lines.iter().map(|l| hasher.signature(l)).collect())
+        .collect();
+
+    assert_eq!(line_clusters(&signatures, &[true, true, true], 0.8), vec![vec![(0, 0), (1, 1), (2, 0)]]);
+    // The copy in a dropped file does not take the place of the first, kept line
+    assert_eq!(line_clusters(&signatures, &[true, false, true], 0.8), vec![vec![(0, 0), (2, 0)]]);
+    assert!(line_clusters(&signatures, &[false, true, false], 0.8).is_empty());
+}
+
+#[test]
+fn test_near_duplicate_clusters() {
+    let texts = [
+        "The quick brown fox jumps over the lazy dog near the river bank",
+        "The quick brown fox jumps over the lazy dog near the river bank!",
+        "Completely different sentence about corpus linguistics and tokenizers",
+        "the quick brown fox jumps over the lazy dog near the river bank",
+        "",
+    ];
+    let hasher = MinHasher::new(7);
+    let signatures: Vec<Vec<u64>> = texts.iter().map(|t| hasher.signature(t)).collect();
+
+    assert_eq!(similarity(&signatures[0], &signatures[3]), 1.0);
+    assert!(similarity(&signatures[0], &signatures[2]) < 0.2);
+    assert!(signatures[4].is_empty());
+
+    let clusters = near_duplicate_clusters(&signatures, 0.8);
+    assert_eq!(clusters, vec![vec![0, 1, 3]]);
+
+    // A large bucket of identical lines is joined into one cluster
+    let same = vec![signatures[0].clone(); 1000];
+    assert_eq!(near_duplicate_clusters(&same, 0.8), vec![(0..1000).collect::<Vec<usize>>()]);
+}
@@ -1,19 +1,35 @@
+use std::collections::HashMap;
+
 pub fn generate_ngrams(lines: Vec<String>, n: usize) -> String {
+    // n-grams never cross a line, so lines (or sentences) shorter than n are skipped
     let mut ngram_vec: Vec<String> = Vec::new();
     for line in lines {
         let words: Vec<String> = line.split_whitespace().map(|s| s.to_string()).collect();
-        // println!("{:?}", words);
         if words.len() < n {
-            return "length of sentences are not enough to form an n-gram.".to_string();
+            continue;
         }
         for i in 0..(words.len() - n + 1) {
             let ngram = &words[i..i + n];
             ngram_vec.push(ngram.join(" ")); // Join the words to form an n-gram
         }
     }
+    if ngram_vec.is_empty() {
+        return "length of sentences are not enough to form an n-gram.".to_string();
+    }
     ngram_vec.join("\n") // Join the n-grams with newlines
 }
 
+pub fn ngram_counts(sequences: &[Vec<String>], n: usize) -> HashMap<Vec<String>, usize> {
+    // Counts of the n-grams within each token sequence; sequences shorter than n are skipped
+    let mut counts = HashMap::new();
+    for tokens in sequences {
+        for gram in tokens.windows(n) {
+            *counts.entry(gram.to_vec()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
 #[test]
 fn test_generate_ngrams() {
     // Define the input data, a vector of strings.
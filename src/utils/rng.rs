@@ -0,0 +1,40 @@
+// Small seeded pseudo-random number generator (SplitMix64) so that runs are reproducible
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Rng {
+        Rng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        // Uniform in [0, 1) from the top 53 bits
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn below(&mut self, n: usize) -> usize {
+        // Uniform integer in 0..n
+        (self.next_f64() * n as f64) as usize
+    }
+}
+
+#[test]
+fn test_rng_is_reproducible() {
+    let mut a = Rng::new(42);
+    let mut b = Rng::new(42);
+    for _ in 0..10 {
+        assert_eq!(a.next_u64(), b.next_u64());
+    }
+    let x = a.next_f64();
+    assert!((0.0..1.0).contains(&x));
+    assert!(a.below(5) < 5);
+}
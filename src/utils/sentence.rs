@@ -0,0 +1,122 @@
+// Abbreviations that end with a period but do not end a sentence (compared in lowercase)
+const ABBREVIATIONS: [&str; 29] = [
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc",
+    "e.g", "i.e", "cf", "al", "fig", "vol", "pp", "ch", "approx",
+    "jan", "feb", "mar", "apr", "aug", "sep", "sept", "oct", "nov", "dec",
+];
+
+fn is_terminator(c: char) -> bool {
+    // Latin-script terminators plus Myanmar, Devanagari, CJK and Arabic ones
+    matches!(c, '.' | '!' | '?' | '…' | '။' | '।' | '॥' | '。' | '！' | '？' | '؟')
+}
+
+fn is_closing(c: char) -> bool {
+    matches!(c, '"' | '\'' | ')' | ']' | '}' | '”' | '’' | '»' | '」' | '』' | '）')
+}
+
+fn is_latin_terminator(c: char) -> bool {
+    matches!(c, '.' | '!' | '?' | '…')
+}
+
+fn is_abbreviation(text: &str, dot: usize, next: Option<char>) -> bool {
+    // The word right before the period, e.g. "Dr" in "Dr. Smith" or "e.g" in "e.g. this"
+    let word = text[..dot]
+        .rsplit(|c: char| c.is_whitespace() || c == '(' || c == '"')
+        .next()
+        .unwrap_or("");
+    let lower = word.to_lowercase();
+
+    // Single letters are initials such as "J. R. R. Tolkien"
+    // and "No." only abbreviates "number" before a digit ("No. 5", but not "I said no. Then")
+    lower.chars().count() == 1 && lower.chars().all(|c| c.is_alphabetic())
+        || ABBREVIATIONS.contains(&lower.as_str())
+        || lower == "no" && matches!(next, Some(n) if n.is_ascii_digit())
+}
+
+pub fn split_sentences(text: &str) -> Vec<String> {
+    // Rule-based sentence segmentation of one block of text
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let mut sentences = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let (pos, c) = chars[i];
+        if !is_terminator(c) {
+            i += 1;
+            continue;
+        }
+
+        // Swallow repeated terminators ("?!", "...") and closing quotes or brackets
+        let mut j = i + 1;
+        while j < chars.len() && (is_terminator(chars[j].1) || is_closing(chars[j].1)) {
+            j += 1;
+        }
+        let end = if j < chars.len() { chars[j].0 } else { text.len() };
+
+        let mut boundary = true;
+        if is_latin_terminator(c) {
+            // Latin terminators need a following space, which keeps "3.14" and "example.com" intact
+            let next = chars.get(j).map(|&(_, n)| n);
+            if matches!(next, Some(n) if !n.is_whitespace()) {
+                boundary = false;
+            } else if c == '.' && j == i + 1 {
+                // A lowercase continuation or a known abbreviation means the period is not final
+                let next_word = chars[j..].iter().map(|&(_, n)| n).find(|n| !n.is_whitespace());
+                if is_abbreviation(text, pos, next_word) || matches!(next_word, Some(n) if n.is_lowercase()) {
+                    boundary = false;
+                }
+            }
+        }
+
+        if boundary {
+            let sentence = text[start..end].trim();
+            if !sentence.is_empty() {
+                sentences.push(sentence.to_string());
+            }
+            start = end;
+        }
+        i = j;
+    }
+
+    let rest = text[start..].trim();
+    if !rest.is_empty() {
+        sentences.push(rest.to_string());
+    }
+
+    sentences
+}
+
+pub fn split_lines(lines: &[String]) -> Vec<String> {
+    // Line breaks are treated as hard sentence boundaries
+    lines.iter().flat_map(|line| split_sentences(line)).collect()
+}
+
+#[test]
+fn test_split_sentences_latin() {
+    let text = "Dr. Smith paid 3.50 dollars, e.g. for tea. He left! Did he?\" Yes";
+    let sentences = split_sentences(text);
+
+    assert_eq!(sentences, vec![
+        "Dr. Smith paid 3.50 dollars, e.g. for tea.",
+        "He left!",
+        "Did he?\"",
+        "Yes",
+    ]);
+}
+
+#[test]
+fn test_split_sentences_no() {
+    assert_eq!(split_sentences("I said no. Then he left."), vec!["I said no.", "Then he left."]);
+    assert_eq!(split_sentences("No. It was me."), vec!["No.", "It was me."]);
+    assert_eq!(split_sentences("See No. 5 on the list."), vec!["See No. 5 on the list."]);
+}
+
+#[test]
+fn test_split_sentences_other_scripts() {
+    assert_eq!(split_sentences("မင်္ဂလာပါ။ နေကောင်းလား။"), vec!["မင်္ဂလာပါ။", "နေကောင်းလား။"]);
+    assert_eq!(split_sentences("नमस्ते। आप कैसे हैं।"), vec!["नमस्ते।", "आप कैसे हैं।"]);
+    assert_eq!(split_sentences("你好。你好吗？"), vec!["你好。", "你好吗？"]);
+    assert_eq!(split_sentences("كيف حالك؟ شكرا"), vec!["كيف حالك؟", "شكرا"]);
+    assert_eq!(split_lines(&["One. Two?! three".to_string(), "".to_string()]).len(), 3);
+}
@@ -0,0 +1,71 @@
+use std::collections::{HashMap, HashSet};
+
+pub fn tf_vectors(freqs: &[&HashMap<String, usize>]) -> Vec<HashMap<String, f64>> {
+    // Raw term frequencies as weights
+    freqs
+        .iter()
+        .map(|freq| freq.iter().map(|(w, &c)| (w.clone(), c as f64)).collect())
+        .collect()
+}
+
+fn norm(vector: &HashMap<String, f64>) -> f64 {
+    vector.values().map(|v| v * v).sum::<f64>().sqrt()
+}
+
+pub fn cosine(a: &HashMap<String, f64>, b: &HashMap<String, f64>) -> f64 {
+    // Iterate over the smaller vector, looking words up in the larger one
+    let (small, large) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    let dot = small.iter().filter_map(|(w, x)| large.get(w).map(|y| x * y)).fold(0.0, |acc, v| acc + v);
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 { 0.0 } else { dot / denominator }
+}
+
+pub fn cosine_matrix(vectors: &[HashMap<String, f64>]) -> Vec<Vec<f64>> {
+    let n = vectors.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let value = if i == j && norm(&vectors[i]) > 0.0 { 1.0 } else { cosine(&vectors[i], &vectors[j]) };
+            matrix[i][j] = value;
+            matrix[j][i] = value;
+        }
+    }
+    matrix
+}
+
+pub fn jaccard_matrix(freqs: &[&HashMap<String, usize>]) -> Vec<Vec<f64>> {
+    // Vocabulary overlap: |A ∩ B| / |A ∪ B|
+    let vocabularies: Vec<HashSet<&String>> = freqs.iter().map(|f| f.keys().collect()).collect();
+    let n = vocabularies.len();
+    let mut matrix = vec![vec![0.0; n]; n];
+    for i in 0..n {
+        for j in i..n {
+            let intersection = vocabularies[i].intersection(&vocabularies[j]).count();
+            let union = vocabularies[i].len() + vocabularies[j].len() - intersection;
+            let value = if union == 0 { 0.0 } else { intersection as f64 / union as f64 };
+            matrix[i][j] = value;
+            matrix[j][i] = value;
+        }
+    }
+    matrix
+}
+
+#[test]
+fn test_similarity_matrices() {
+    let mut doc1 = HashMap::new();
+    doc1.insert("a".to_string(), 1);
+    doc1.insert("b".to_string(), 1);
+    let mut doc2 = HashMap::new();
+    doc2.insert("b".to_string(), 1);
+    doc2.insert("c".to_string(), 1);
+
+    let freqs = [&doc1, &doc2];
+    let cos = cosine_matrix(&tf_vectors(&freqs));
+    assert_eq!(cos[0][0], 1.0);
+    assert!((cos[0][1] - 0.5).abs() < 1e-12);
+    assert_eq!(cos[1][0], cos[0][1]);
+
+    let jac = jaccard_matrix(&freqs);
+    assert!((jac[0][1] - 1.0 / 3.0).abs() < 1e-12);
+    assert_eq!(jac[1][1], 1.0);
+}
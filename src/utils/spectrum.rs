@@ -0,0 +1,74 @@
+use std::collections::HashMap;
+
+// Default for --spectrum-max: frequencies listed individually in the spectrum; more frequent
+// types are pooled into one row
+pub const SPECTRUM_MAX: usize = 10;
+// Number of hapax legomena shown as examples
+pub const HAPAX_SAMPLES: usize = 20;
+
+#[derive(Debug)]
+pub struct SpectrumRow {
+    pub frequency: usize,
+    pub types: usize,
+    pub share: f64,
+}
+
+#[derive(Debug)]
+pub struct Spectrum {
+    // V(m) for m = 1..=max_frequency, then every type seen more often pooled together
+    pub rows: Vec<SpectrumRow>,
+    pub rest: SpectrumRow,
+    pub hapax_samples: Vec<String>,
+}
+
+pub fn frequency_spectrum(freq: &HashMap<String, usize>, max_frequency: usize, samples: usize) -> Spectrum {
+    // Number of word types that occur exactly m times, and their share of the vocabulary
+    let vocabulary = freq.len();
+    let share = |types: usize| {
+        if vocabulary == 0 { 0.0 } else { types as f64 / vocabulary as f64 * 100.0 }
+    };
+
+    let mut by_frequency = vec![0; max_frequency + 1];
+    let mut rest = 0;
+    for &f in freq.values() {
+        if f <= max_frequency {
+            by_frequency[f] += 1;
+        } else {
+            rest += 1;
+        }
+    }
+
+    let rows = (1..=max_frequency)
+        .map(|m| SpectrumRow { frequency: m, types: by_frequency[m], share: share(by_frequency[m]) })
+        .collect();
+
+    // Spread the samples evenly over the alphabetically sorted hapaxes
+    let mut hapaxes: Vec<&String> = freq.iter().filter(|(_, &f)| f == 1).map(|(w, _)| w).collect();
+    hapaxes.sort();
+    let step = (hapaxes.len() / samples.max(1)).max(1);
+    let hapax_samples = hapaxes.iter().step_by(step).take(samples).map(|w| w.to_string()).collect();
+
+    Spectrum {
+        rows,
+        rest: SpectrumRow { frequency: max_frequency + 1, types: rest, share: share(rest) },
+        hapax_samples,
+    }
+}
+
+#[test]
+fn test_frequency_spectrum() {
+    let mut freq = HashMap::new();
+    for (word, f) in [("a", 1), ("b", 1), ("c", 2), ("d", 5)] {
+        freq.insert(word.to_string(), f);
+    }
+
+    let spectrum = frequency_spectrum(&freq, 3, 10);
+
+    assert_eq!(spectrum.rows.len(), 3);
+    assert_eq!(spectrum.rows[0].types, 2);
+    assert_eq!(spectrum.rows[0].share, 50.0);
+    assert_eq!(spectrum.rows[1].types, 1);
+    assert_eq!(spectrum.rows[2].types, 0);
+    assert_eq!(spectrum.rest.types, 1);
+    assert_eq!(spectrum.hapax_samples, vec!["a", "b"]);
+}
@@ -0,0 +1,405 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::lang_detect::{return_lang, Lang};
+use crate::word_freq;
+
+// Languages with a stemmer: Porter for English, light Snowball-style suffix stripping for the rest
+pub const LANGS: [Lang; 18] = [
+    Lang::English, Lang::Spanish, Lang::French, Lang::German, Lang::Portuguese, Lang::Italian,
+    Lang::Dutch, Lang::Swedish, Lang::Norwegian, Lang::Danish, Lang::Finnish, Lang::Romanian,
+    Lang::Hungarian, Lang::Turkish, Lang::Polish, Lang::Czech, Lang::Malay, Lang::Croatian,
+];
+
+pub fn names() -> Vec<String> {
+    // Accepted --stem values
+    LANGS.iter().map(|&lang| return_lang(lang)).collect()
+}
+
+pub fn parse_lang(name: &str) -> Lang {
+    // Language name as printed in the reports, e.g. "English"; the command line only lets through names()
+    let name = name.trim().to_lowercase();
+    *LANGS
+        .iter()
+        .find(|&&lang| return_lang(lang).to_lowercase() == name)
+        .unwrap_or_else(|| panic!("No stemmer for '{}'.", name))
+}
+
+// ---- Porter (1980) stemmer for English ----
+
+fn is_consonant(w: &[u8], i: usize) -> bool {
+    match w[i] {
+        b'a' | b'e' | b'i' | b'o' | b'u' => false,
+        b'y' => i == 0 || !is_consonant(w, i - 1),
+        _ => true,
+    }
+}
+
+fn measure(w: &[u8]) -> usize {
+    // Number of vowel-consonant sequences: [C](VC)^m[V]
+    let mut m = 0;
+    let mut previous_vowel = false;
+    for i in 0..w.len() {
+        let vowel = !is_consonant(w, i);
+        if previous_vowel && !vowel {
+            m += 1;
+        }
+        previous_vowel = vowel;
+    }
+    m
+}
+
+fn has_vowel(w: &[u8]) -> bool {
+    (0..w.len()).any(|i| !is_consonant(w, i))
+}
+
+fn ends_double_consonant(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 2 && w[n - 1] == w[n - 2] && is_consonant(w, n - 1)
+}
+
+fn ends_cvc(w: &[u8]) -> bool {
+    // consonant-vowel-consonant, where the last consonant is not w, x or y
+    let n = w.len();
+    n >= 3
+        && is_consonant(w, n - 3)
+        && !is_consonant(w, n - 2)
+        && is_consonant(w, n - 1)
+        && !matches!(w[n - 1], b'w' | b'x' | b'y')
+}
+
+fn longest_suffix<'a>(w: &[u8], rules: &'a [(&'a str, &'a str)]) -> Option<&'a (&'a str, &'a str)> {
+    rules
+        .iter()
+        .filter(|(suffix, _)| w.ends_with(suffix.as_bytes()))
+        .max_by_key(|(suffix, _)| suffix.len())
+}
+
+fn replace_if(w: &mut Vec<u8>, rules: &[(&str, &str)], min_measure: usize) {
+    // Only the longest matching suffix is considered, and replaced when the stem's measure is high enough
+    if let Some((suffix, replacement)) = longest_suffix(w, rules) {
+        let stem_len = w.len() - suffix.len();
+        let stem = &w[..stem_len];
+        let ion_ok = *suffix != "ion" || stem.ends_with(b"s") || stem.ends_with(b"t");
+        if measure(stem) > min_measure && ion_ok {
+            w.truncate(stem_len);
+            w.extend_from_slice(replacement.as_bytes());
+        }
+    }
+}
+
+const STEP2: [(&str, &str); 21] = [
+    ("ational", "ate"), ("tional", "tion"), ("enci", "ence"), ("anci", "ance"), ("izer", "ize"),
+    ("bli", "ble"), ("alli", "al"), ("entli", "ent"), ("eli", "e"), ("ousli", "ous"), ("ization", "ize"),
+    ("ation", "ate"), ("ator", "ate"), ("alism", "al"), ("iveness", "ive"), ("fulness", "ful"),
+    ("ousness", "ous"), ("aliti", "al"), ("iviti", "ive"), ("biliti", "ble"), ("logi", "log"),
+];
+
+const STEP3: [(&str, &str); 7] = [
+    ("icate", "ic"), ("ative", ""), ("alize", "al"), ("iciti", "ic"), ("ical", "ic"), ("ful", ""), ("ness", ""),
+];
+
+const STEP4: [(&str, &str); 19] = [
+    ("al", ""), ("ance", ""), ("ence", ""), ("er", ""), ("ic", ""), ("able", ""), ("ible", ""), ("ant", ""),
+    ("ement", ""), ("ment", ""), ("ent", ""), ("ion", ""), ("ou", ""), ("ism", ""), ("ate", ""), ("iti", ""),
+    ("ous", ""), ("ive", ""), ("ize", ""),
+];
+
+pub fn porter(word: &str) -> String {
+    if word.len() <= 2 || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+    let mut w = word.as_bytes().to_vec();
+
+    // Step 1a: plurals
+    if w.ends_with(b"sses") || w.ends_with(b"ies") {
+        w.truncate(w.len() - 2);
+    } else if !w.ends_with(b"ss") && w.ends_with(b"s") {
+        w.pop();
+    }
+
+    // Step 1b: -eed, -ed, -ing
+    if w.ends_with(b"eed") {
+        if measure(&w[..w.len() - 3]) > 0 {
+            w.pop();
+        }
+    } else {
+        let removed = [&b"ed"[..], &b"ing"[..]]
+            .iter()
+            .find(|s| w.ends_with(s) && has_vowel(&w[..w.len() - s.len()]))
+            .map(|s| s.len());
+        if let Some(len) = removed {
+            w.truncate(w.len() - len);
+            if w.ends_with(b"at") || w.ends_with(b"bl") || w.ends_with(b"iz") {
+                w.push(b'e');
+            } else if ends_double_consonant(&w) && !matches!(w[w.len() - 1], b'l' | b's' | b'z') {
+                w.pop();
+            } else if measure(&w) == 1 && ends_cvc(&w) {
+                w.push(b'e');
+            }
+        }
+    }
+
+    // Step 1c: y -> i
+    if w.ends_with(b"y") && has_vowel(&w[..w.len() - 1]) {
+        let n = w.len();
+        w[n - 1] = b'i';
+    }
+
+    replace_if(&mut w, &STEP2, 0);
+    replace_if(&mut w, &STEP3, 0);
+    replace_if(&mut w, &STEP4, 1);
+
+    // Step 5: final -e and -ll
+    if w.ends_with(b"e") {
+        let stem = &w[..w.len() - 1];
+        let m = measure(stem);
+        if m > 1 || (m == 1 && !ends_cvc(stem)) {
+            w.pop();
+        }
+    }
+    if measure(&w) > 1 && w.ends_with(b"ll") {
+        w.pop();
+    }
+
+    String::from_utf8(w).unwrap()
+}
+
+// ---- Light stemmers for the other Latin-script languages ----
+
+fn vowels(lang: Lang) -> &'static str {
+    match lang {
+        Lang::Spanish => "aeiouáéíóúü",
+        Lang::French => "aeiouyâàëéêèïîôûù",
+        Lang::German => "aeiouyäöü",
+        Lang::Portuguese => "aeiouáéíóúâêôãõ",
+        Lang::Italian => "aeiouàèìòù",
+        Lang::Dutch => "aeiouyè",
+        Lang::Swedish | Lang::Norwegian | Lang::Danish => "aeiouyäåöæø",
+        Lang::Finnish => "aeiouyäö",
+        Lang::Romanian => "aeiouăâî",
+        Lang::Hungarian => "aeiouáéíóöőúüű",
+        Lang::Turkish => "aeıioöuü",
+        Lang::Polish => "aeiouyąęó",
+        Lang::Czech => "aeiouyáéěíóúůý",
+        _ => "aeiou",
+    }
+}
+
+fn suffixes(lang: Lang) -> &'static [&'static str] {
+    // Inflectional and common derivational endings, after the Snowball stemmers where one exists
+    match lang {
+        Lang::Spanish => &[
+            "amientos", "imientos", "amiento", "imiento", "aciones", "uciones", "adoras", "adores", "ancias", "encias",
+            "idades", "mente", "ación", "ución", "adora", "ador", "ancia", "encia", "idad", "ismos", "istas", "ables",
+            "ibles", "ismo", "ista", "able", "ible", "osos", "osas", "oso", "osa", "ivas", "ivos", "iva", "ivo",
+            "ando", "iendo", "ados", "adas", "idos", "idas", "ado", "ada", "ido", "ida", "aron", "ieron", "ar", "er",
+            "ir", "es", "as", "os", "a", "o", "e", "s",
+        ],
+        Lang::French => &[
+            "issements", "issement", "atrices", "atrice", "ateurs", "ateur", "ations", "ation", "logies", "logie",
+            "ements", "ement", "ités", "ité", "ives", "ive", "ifs", "if", "euses", "euse", "eux", "ances", "ance",
+            "ences", "ence", "ismes", "isme", "istes", "iste", "ables", "able", "ment", "aient", "ions", "ons",
+            "ais", "ait", "ant", "ées", "ée", "és", "é", "er", "ez", "es", "e", "s",
+        ],
+        Lang::German => &[
+            "ungen", "heiten", "keiten", "lichen", "liche", "lich", "heit", "keit", "isch", "ung", "end", "ern", "em",
+            "en", "er", "es", "ig", "ik", "e", "s",
+        ],
+        Lang::Portuguese => &[
+            "amentos", "imentos", "amento", "imento", "adoras", "adores", "ências", "idades", "ações", "ação",
+            "adora", "ador", "ância", "ência", "idade", "mente", "ismos", "ismo", "istas", "ista", "áveis", "ável",
+            "íveis", "ível", "osos", "osas", "oso", "osa", "ivas", "ivos", "iva", "ivo", "ando", "endo", "indo",
+            "ados", "adas", "idos", "idas", "ado", "ada", "ido", "ida", "ar", "er", "ir", "es", "as", "os", "a", "o",
+            "e", "s",
+        ],
+        Lang::Italian => &[
+            "amenti", "imenti", "amento", "imento", "azioni", "azione", "atrici", "atrice", "atori", "atore", "abili",
+            "abile", "ibili", "ibile", "mente", "anze", "anza", "enze", "enza", "ismi", "ismo", "isti", "ista", "ando",
+            "endo", "ità", "osi", "ose", "oso", "osa", "ivi", "ive", "ivo", "iva", "ato", "ata", "ati", "ate", "uto",
+            "uta", "uti", "ute", "ito", "ita", "iti", "ite", "are", "ere", "ire", "i", "e", "a", "o",
+        ],
+        Lang::Dutch => &[
+            "heden", "ingen", "heid", "lijk", "baar", "ende", "ing", "bar", "end", "ene", "ers", "en", "er", "es",
+            "e", "s",
+        ],
+        Lang::Swedish => &[
+            "heterna", "hetens", "heten", "heter", "arnas", "ernas", "ornas", "andes", "arens", "andet", "arna",
+            "erna", "orna", "ande", "arne", "aste", "aren", "ades", "erns", "ade", "are", "ern", "ens", "het", "ast",
+            "ad", "en", "ar", "er", "or", "as", "es", "at", "a", "e", "s",
+        ],
+        Lang::Norwegian => &[
+            "hetenes", "hetene", "hetens", "heter", "heten", "endes", "ande", "ende", "edes", "enes", "erte", "ert",
+            "ast", "ane", "ene", "ens", "ers", "ets", "het", "en", "ar", "er", "as", "es", "et", "a", "e", "s",
+        ],
+        Lang::Danish => &[
+            "erendes", "erende", "hedens", "ethed", "erede", "heden", "heder", "endes", "ernes", "erens", "erets",
+            "ered", "ende", "erne", "eren", "erer", "heds", "enes", "eres", "eret", "hed", "ene", "ere", "ens", "ers",
+            "ets", "en", "er", "es", "et", "e", "s",
+        ],
+        Lang::Finnish => &[
+            "isiin", "ineen", "itten", "ihin", "issa", "issä", "ista", "istä", "illa", "illä", "ilta", "iltä", "ille",
+            "iksi", "iden", "ssa", "ssä", "sta", "stä", "lla", "llä", "lta", "ltä", "lle", "ksi", "jen", "ien", "den",
+            "na", "nä", "ta", "tä", "in", "en", "an", "än", "a", "ä", "n", "t",
+        ],
+        Lang::Romanian => &[
+            "ațiuni", "ațiune", "ități", "itate", "ismul", "isme", "iști", "abile", "ibile", "abil", "ibil", "ului",
+            "ilor", "elor", "ist", "ele", "lor", "ul", "ii", "le", "ea", "a", "e", "i", "ă",
+        ],
+        Lang::Hungarian => &[
+            "okban", "ekben", "ában", "ében", "ként", "ban", "ben", "nak", "nek", "val", "vel", "ból", "ből", "ról",
+            "ről", "tól", "től", "hoz", "hez", "höz", "nál", "nél", "ra", "re", "ba", "be", "on", "en", "ön", "ok",
+            "ek", "ak", "ot", "et", "at", "t", "k",
+        ],
+        Lang::Turkish => &[
+            "lerinden", "larından", "lerinde", "larında", "lerine", "larına", "lerin", "ların", "leri", "ları",
+            "nden", "ndan", "ler", "lar", "den", "dan", "ten", "tan", "nin", "nın", "nun", "nün", "de", "da", "te",
+            "ta", "in", "ın", "un", "ün", "yi", "yı", "yu", "yü", "i", "ı", "u", "ü", "e", "a",
+        ],
+        Lang::Polish => &[
+            "owania", "owanie", "ościami", "ością", "ości", "ość", "ami", "ach", "ego", "emu", "owi", "ów", "om",
+            "ie", "ej", "ym", "im", "ą", "ę", "a", "e", "i", "o", "u", "y",
+        ],
+        Lang::Czech => &[
+            "ování", "ostí", "ost", "ích", "ých", "ami", "emi", "ovi", "ové", "ého", "ému", "ým", "ím", "ům", "ou",
+            "em", "ů", "a", "e", "i", "o", "u", "y", "á", "é", "í", "ý",
+        ],
+        Lang::Malay => &["nya", "lah", "kah", "kan", "an", "i"],
+        Lang::Croatian => &["ama", "ima", "ovi", "eva", "om", "em", "ih", "og", "oj", "a", "e", "i", "o", "u"],
+        _ => &[],
+    }
+}
+
+fn r1_start(chars: &[char], vowels: &str) -> usize {
+    // Snowball R1: after the first non-vowel that follows a vowel, but at least 3 letters in
+    let mut start = chars.len();
+    for i in 1..chars.len() {
+        if !vowels.contains(chars[i]) && vowels.contains(chars[i - 1]) {
+            start = i + 1;
+            break;
+        }
+    }
+    start.max(3)
+}
+
+pub fn light_stem(word: &str, lang: Lang) -> String {
+    // Remove the longest listed suffix that lies inside R1
+    let chars: Vec<char> = word.chars().collect();
+    let r1 = r1_start(&chars, vowels(lang));
+
+    let best = suffixes(lang)
+        .iter()
+        .filter(|s| word.ends_with(*s) && chars.len() - s.chars().count() >= r1)
+        .max_by_key(|s| s.chars().count());
+    match best {
+        Some(s) => chars[..chars.len() - s.chars().count()].iter().collect(),
+        None => word.to_string(),
+    }
+}
+
+pub fn stem(word: &str, lang: Lang) -> String {
+    if lang == Lang::English { porter(word) } else { light_stem(word, lang) }
+}
+
+pub fn load_lemmas(lines: &[String]) -> HashMap<String, String> {
+    // "form lemma" per line (tab or space separated), lowercased; blank lines and '#' comments are skipped
+    lines
+        .iter()
+        .filter(|line| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() >= 2 {
+                Some((fields[0].to_lowercase(), fields[1].to_lowercase()))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+pub struct Normalizer {
+    stemmer: Option<Lang>,
+    lemmas: HashMap<String, String>,
+}
+
+impl Normalizer {
+    pub fn new(stemmer: Option<Lang>, lemmas: HashMap<String, String>) -> Normalizer {
+        Normalizer { stemmer, lemmas }
+    }
+
+    pub fn word(&self, word: &str) -> String {
+        // The lemma table wins; words not in it are stemmed
+        if let Some(lemma) = self.lemmas.get(word) {
+            return lemma.clone();
+        }
+        match self.stemmer {
+            Some(lang) => stem(word, lang),
+            None => word.to_string(),
+        }
+    }
+
+    pub fn freq(&self, freq: &HashMap<String, usize>) -> HashMap<String, usize> {
+        // Frequencies summed by stem or lemma
+        let mut merged = HashMap::new();
+        for (word, &count) in freq {
+            *merged.entry(self.word(word)).or_insert(0) += count;
+        }
+        merged
+    }
+
+    pub fn set(&self, words: &HashSet<String>) -> HashSet<String> {
+        // A word list (e.g. stopwords) in the same form as normalized text, so it still matches it
+        words.iter().map(|w| self.word(w)).collect()
+    }
+
+    pub fn line(&self, line: &str) -> String {
+        // Tokens of a line replaced by their stem or lemma
+        word_freq::tokenize(&[line.to_string()])
+            .iter()
+            .map(|w| self.word(w))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+#[test]
+fn test_porter() {
+    let cases = [
+        ("caresses", "caress"), ("ponies", "poni"), ("cats", "cat"), ("feed", "feed"), ("agreed", "agre"),
+        ("plastered", "plaster"), ("motoring", "motor"), ("sing", "sing"), ("hopping", "hop"), ("falling", "fall"),
+        ("filing", "file"), ("happy", "happi"), ("relational", "relat"), ("generalization", "gener"),
+        ("test", "test"), ("tests", "test"), ("testing", "test"), ("adjustable", "adjust"), ("controll", "control"),
+    ];
+    for (word, expected) in cases {
+        assert_eq!(porter(word), expected, "{}", word);
+    }
+}
+
+#[test]
+fn test_normalizer() {
+    assert_eq!(light_stem("canciones", Lang::Spanish), "cancion");
+    assert_eq!(light_stem("rápidamente", Lang::Spanish), "rápida");
+    assert_eq!(light_stem("häuser", Lang::German), "häus");
+    assert_eq!(parse_lang("english"), Lang::English);
+    assert_eq!(names().len(), LANGS.len());
+
+    let lemmas = load_lemmas(&["# form lemma".to_string(), "went\tgo".to_string(), "mice mouse".to_string()]);
+    let normalizer = Normalizer::new(Some(Lang::English), lemmas);
+    assert_eq!(normalizer.line("Went testing, mice!"), "go test mouse");
+
+    // Stopwords are matched after stemming ("this was" -> "thi wa")
+    let stopwords: HashSet<String> = ["this", "was", "has"].iter().map(|w| w.to_string()).collect();
+    let stemmed = normalizer.set(&stopwords);
+    let kept: Vec<String> = normalizer
+        .line("This was a test that has passed")
+        .split_whitespace()
+        .filter(|w| !stemmed.contains(*w))
+        .map(|w| w.to_string())
+        .collect();
+    assert_eq!(kept, vec!["a", "test", "that", "pass"]);
+
+    let mut freq = HashMap::new();
+    freq.insert("test".to_string(), 1);
+    freq.insert("tests".to_string(), 2);
+    freq.insert("testing".to_string(), 3);
+    assert_eq!(normalizer.freq(&freq).get("test"), Some(&6));
+}
@@ -0,0 +1,362 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::lang_detect::{return_lang, Lang, LangInfo};
+
+// Languages with a bundled stopword list; Thai, Kurdish, Tamil, Punjabi (Gurmukhi), Kannada, Amharic,
+// Yoruba, Zulu, Uzbek, Sinhala, Belarusian, Azerbaijani, Javanese, Burmese, Telugu and Malayalam have none
+pub const LANGS: [Lang; 40] = [
+    Lang::English, Lang::Spanish, Lang::French, Lang::German, Lang::ChineseSimplified,
+    Lang::Japanese, Lang::Russian, Lang::Arabic, Lang::Portuguese, Lang::Italian, Lang::Dutch,
+    Lang::Swedish, Lang::Korean, Lang::Turkish, Lang::Greek, Lang::Hindi, Lang::Vietnamese,
+    Lang::Finnish, Lang::Norwegian, Lang::Danish, Lang::Romanian, Lang::Polish, Lang::Hungarian,
+    Lang::Hebrew, Lang::Czech, Lang::Serbian, Lang::Filipino, Lang::Ukrainian, Lang::Malay,
+    Lang::Bengali, Lang::Swahili, Lang::Persian, Lang::Afrikaans, Lang::Bulgarian, Lang::Croatian,
+    Lang::Slovenian, Lang::Estonian, Lang::Icelandic, Lang::Urdu, Lang::Nepali,
+];
+
+pub fn bundled(lang: Lang) -> &'static [&'static str] {
+    // Short lists of the most frequent function words, lowercased like word_freq::tokenize output
+    match lang {
+        Lang::English => &[
+            "a", "about", "after", "all", "also", "an", "and", "any", "are", "as", "at", "be", "been", "but", "by",
+            "can", "could", "did", "do", "does", "for", "from", "had", "has", "have", "he", "her", "his", "i", "if",
+            "in", "into", "is", "it", "its", "me", "my", "no", "not", "of", "on", "or", "our", "she", "so", "that",
+            "the", "their", "them", "then", "there", "these", "they", "this", "to", "too", "us", "was", "we", "were",
+            "what", "when", "which", "who", "will", "with", "would", "you", "your",
+        ],
+        Lang::Spanish => &[
+            "a", "al", "como", "con", "de", "del", "el", "ella", "en", "es", "esta", "este", "fue", "ha", "la", "las",
+            "le", "lo", "los", "más", "me", "mi", "no", "o", "para", "pero", "por", "que", "se", "si", "sin", "su",
+            "sus", "te", "tu", "un", "una", "y", "ya", "yo",
+        ],
+        Lang::French => &[
+            "à", "au", "aux", "avec", "ce", "ces", "dans", "de", "des", "du", "elle", "en", "est", "et", "il", "ils",
+            "je", "la", "le", "les", "leur", "lui", "ma", "mais", "me", "mon", "ne", "nous", "on", "ou", "par", "pas",
+            "pour", "qu", "que", "qui", "sa", "se", "ses", "son", "sur", "ta", "te", "tu", "un", "une", "vous",
+        ],
+        Lang::German => &[
+            "aber", "als", "am", "an", "auch", "auf", "aus", "bei", "bin", "bis", "das", "dass", "dem", "den", "der",
+            "des", "die", "du", "ein", "eine", "einen", "einer", "er", "es", "für", "hat", "ich", "ihr", "im", "in",
+            "ist", "mit", "nach", "nicht", "noch", "oder", "sich", "sie", "sind", "so", "um", "und", "von", "war",
+            "wie", "wir", "zu", "zum", "zur",
+        ],
+        Lang::ChineseSimplified => &[
+            "的", "了", "和", "是", "在", "我", "你", "他", "她", "它", "们", "这", "那", "也", "就", "都", "而", "及",
+            "与", "着", "或", "一个", "没有", "我们", "你们", "他们",
+        ],
+        Lang::Japanese => &[
+            "の", "に", "は", "を", "た", "が", "で", "て", "と", "し", "れ", "さ", "ある", "いる", "する", "から",
+            "な", "こと", "として", "も", "や", "など", "なっ", "です", "ます",
+        ],
+        Lang::Russian => &[
+            "а", "без", "был", "была", "были", "было", "в", "вы", "да", "для", "до", "его", "ее", "её", "если", "же",
+            "за", "и", "из", "или", "их", "к", "как", "когда", "ли", "мы", "на", "не", "но", "о", "он", "она", "они",
+            "от", "по", "с", "так", "то", "только", "ты", "у", "уже", "что", "это", "я",
+        ],
+        Lang::Arabic => &[
+            "في", "من", "على", "إلى", "عن", "مع", "أن", "إن", "ما", "لا", "هذا", "هذه", "ذلك", "التي", "الذي",
+            "هو", "هي", "كان", "كانت", "قد", "و", "أو", "ثم", "كل", "بين", "أي",
+        ],
+        Lang::Portuguese => &[
+            "a", "ao", "as", "com", "como", "da", "das", "de", "do", "dos", "e", "ela", "ele", "em", "era", "essa",
+            "esse", "está", "eu", "foi", "isso", "já", "mais", "mas", "me", "na", "não", "no", "nos", "o", "os", "ou",
+            "para", "pela", "pelo", "por", "que", "se", "sem", "seu", "sua", "um", "uma", "você",
+        ],
+        Lang::Italian => &[
+            "a", "al", "alla", "che", "ci", "come", "con", "da", "del", "della", "di", "e", "è", "gli", "ha", "i",
+            "il", "in", "io", "la", "le", "lei", "lo", "lui", "ma", "mi", "ne", "nel", "non", "per", "più", "se",
+            "si", "sono", "su", "ti", "tu", "un", "una", "uno",
+        ],
+        Lang::Dutch => &[
+            "aan", "al", "als", "bij", "dan", "dat", "de", "die", "dit", "een", "en", "er", "had", "heb", "het", "hij",
+            "hoe", "ik", "in", "is", "je", "maar", "me", "met", "mij", "na", "niet", "nog", "of", "om", "ook", "op",
+            "te", "tot", "u", "uit", "van", "voor", "was", "wat", "we", "wel", "zich", "zij", "zijn", "ze",
+        ],
+        Lang::Swedish => &[
+            "att", "av", "de", "den", "det", "du", "där", "efter", "en", "ett", "för", "från", "han", "har", "hon",
+            "i", "inte", "jag", "kan", "man", "med", "men", "mig", "nu", "och", "om", "på", "sig", "som", "så",
+            "till", "under", "var", "vi", "är",
+        ],
+        Lang::Korean => &[
+            "이", "그", "저", "것", "수", "등", "및", "더", "또", "그리고", "하지만", "그러나", "이런", "그런", "있다",
+            "하다", "있는", "없는", "나", "너", "우리",
+        ],
+        Lang::Turkish => &[
+            "acaba", "ama", "bir", "biz", "bu", "da", "de", "daha", "diye", "en", "gibi", "hem", "her", "için", "ile",
+            "ise", "kadar", "ki", "mi", "mı", "ne", "o", "olan", "olarak", "sen", "siz", "şu", "ve", "veya", "ya",
+        ],
+        Lang::Greek => &[
+            "αλλά", "από", "για", "δεν", "είναι", "εγώ", "θα", "και", "με", "μη", "μια", "να", "ο", "οι", "που",
+            "σε", "στη", "στην", "στο", "στον", "τα", "την", "της", "τι", "το", "τον", "του", "των", "ένα", "ή",
+        ],
+        Lang::Hindi => &[
+            "और", "का", "की", "के", "को", "है", "हैं", "था", "थी", "थे", "में", "से", "पर", "यह", "वह", "ये", "वे",
+            "भी", "तो", "ही", "न", "नहीं", "एक", "कि", "जो", "कर", "हो", "गया",
+        ],
+        Lang::Vietnamese => &[
+            "và", "của", "có", "là", "được", "cho", "không", "những", "các", "một", "này", "với", "trong", "đã",
+            "để", "thì", "người", "khi", "cũng", "như", "đó", "ở", "tôi", "bạn",
+        ],
+        Lang::Finnish => &[
+            "ei", "että", "hän", "he", "ja", "jo", "joka", "jos", "kun", "me", "mikä", "minä", "mutta", "myös", "ne",
+            "niin", "nyt", "on", "ovat", "se", "sen", "sinä", "tai", "tämä", "te", "vain", "oli",
+        ],
+        Lang::Norwegian => &[
+            "at", "av", "de", "den", "det", "du", "en", "er", "et", "for", "fra", "han", "har", "hun", "i", "ikke",
+            "jeg", "med", "men", "meg", "og", "om", "på", "seg", "som", "så", "til", "var", "vi",
+        ],
+        Lang::Danish => &[
+            "af", "at", "de", "den", "der", "det", "du", "en", "er", "et", "for", "fra", "han", "har", "hun", "i",
+            "ikke", "jeg", "med", "men", "mig", "og", "om", "på", "sig", "som", "så", "til", "var", "vi",
+        ],
+        Lang::Romanian => &[
+            "a", "ai", "al", "ale", "cu", "ce", "care", "cum", "da", "de", "din", "e", "este", "eu", "în", "la",
+            "mai", "nu", "o", "pe", "pentru", "sa", "să", "se", "și", "un", "una", "unei", "ca", "sunt",
+        ],
+        Lang::Polish => &[
+            "a", "ale", "by", "co", "czy", "dla", "do", "i", "ja", "jak", "jest", "już", "na", "nie", "o", "od", "po",
+            "się", "są", "ta", "tak", "te", "to", "ten", "w", "we", "z", "za", "że",
+        ],
+        Lang::Hungarian => &[
+            "a", "az", "de", "egy", "el", "és", "hogy", "is", "meg", "mint", "nem", "ő", "ők", "sem", "van", "volt",
+            "vagy", "én", "te", "mi", "ti", "ez", "azt", "csak", "már",
+        ],
+        Lang::Hebrew => &[
+            "של", "את", "על", "עם", "זה", "זו", "הוא", "היא", "הם", "אני", "אתה", "לא", "כן", "גם", "אבל", "או",
+            "כי", "מה", "אם", "כל", "יש", "אין", "היה",
+        ],
+        Lang::Czech => &[
+            "a", "ale", "by", "do", "i", "jak", "je", "jsem", "jsou", "k", "na", "ne", "o", "od", "po", "pro", "s",
+            "se", "si", "tak", "to", "v", "ve", "z", "za", "že",
+        ],
+        Lang::Serbian => &[
+            "и", "у", "на", "је", "да", "се", "за", "од", "са", "не", "то", "а", "али", "као", "из", "су", "би",
+            "i", "u", "na", "je", "da", "se", "za", "od", "sa", "ne", "to", "a", "ali", "kao", "iz", "su",
+        ],
+        Lang::Filipino => &[
+            "ang", "ng", "mga", "sa", "na", "at", "ay", "si", "ni", "kay", "ko", "mo", "siya", "ako", "ikaw", "ito",
+            "iyon", "para", "pero", "hindi", "may", "din", "rin",
+        ],
+        Lang::Ukrainian => &[
+            "а", "але", "в", "від", "він", "вона", "вони", "до", "з", "за", "і", "й", "на", "не", "про", "та", "так",
+            "те", "ти", "у", "це", "що", "як", "я", "ми", "ви",
+        ],
+        Lang::Malay => &[
+            "ada", "adalah", "akan", "dan", "dari", "dengan", "di", "ini", "itu", "ke", "kami", "kita", "mereka",
+            "pada", "saya", "tidak", "untuk", "yang", "juga", "atau", "anda",
+        ],
+        Lang::Bengali => &[
+            "এবং", "ও", "কি", "কে", "না", "এই", "সেই", "তার", "আমি", "তুমি", "আমরা", "তারা", "হয়", "ছিল", "করে",
+            "থেকে", "জন্য", "একটি", "যে", "কিন্তু",
+        ],
+        Lang::Swahili => &[
+            "na", "ya", "wa", "za", "kwa", "la", "katika", "ni", "kama", "hii", "huo", "hiyo", "lakini", "au", "si",
+            "mimi", "wewe", "yeye", "sisi", "wao",
+        ],
+        Lang::Persian => &[
+            "و", "در", "به", "از", "که", "این", "آن", "را", "با", "است", "برای", "یک", "تا", "هم", "من", "تو",
+            "او", "ما", "شما", "آنها", "بود", "شد",
+        ],
+        Lang::Afrikaans => &[
+            "die", "en", "is", "in", "van", "het", "nie", "te", "dat", "op", "vir", "met", "ek", "jy", "hy", "sy",
+            "ons", "hulle", "was", "om", "maar", "ook",
+        ],
+        Lang::Bulgarian => &[
+            "а", "в", "във", "да", "до", "е", "за", "и", "из", "като", "на", "не", "но", "от", "по", "с", "се", "си",
+            "са", "то", "той", "тя", "те", "че", "аз", "ти", "ние",
+        ],
+        Lang::Croatian => &[
+            "a", "ali", "da", "do", "i", "iz", "je", "kao", "na", "ne", "od", "sa", "se", "su", "to", "u", "za",
+            "što", "ili", "ja", "ti", "on", "ona",
+        ],
+        Lang::Slovenian => &[
+            "in", "je", "na", "se", "da", "so", "za", "ki", "v", "z", "s", "pa", "ne", "ali", "bi", "to", "od", "po",
+            "tudi", "kot", "jaz", "ti", "on", "ona",
+        ],
+        Lang::Estonian => &[
+            "ja", "on", "ei", "et", "see", "ka", "kui", "mis", "ta", "nad", "me", "te", "ma", "sa", "oli", "aga",
+            "või", "nii", "siis", "veel",
+        ],
+        Lang::Icelandic => &[
+            "og", "að", "í", "á", "er", "sem", "til", "ekki", "með", "það", "en", "var", "um", "af", "hann", "hún",
+            "ég", "þú", "við", "þeir",
+        ],
+        Lang::Urdu => &[
+            "اور", "کا", "کی", "کے", "کو", "ہے", "ہیں", "تھا", "تھی", "میں", "سے", "پر", "یہ", "وہ", "بھی", "نہیں",
+            "ایک", "کہ", "جو",
+        ],
+        Lang::Nepali => &[
+            "र", "को", "का", "की", "मा", "ले", "लाई", "छ", "छन्", "थियो", "यो", "त्यो", "पनि", "भने", "एक", "हो",
+            "गर्न", "नै",
+        ],
+        _ => &[],
+    }
+}
+
+// Languages with a list that are written in the Latin script, which lang_detect cannot tell apart
+// (its Latin ranges are reported as English, Spanish, French, Romanian or Vietnamese)
+const LATIN: [Lang; 26] = [
+    Lang::English, Lang::Spanish, Lang::French, Lang::German, Lang::Portuguese, Lang::Italian, Lang::Dutch,
+    Lang::Swedish, Lang::Turkish, Lang::Vietnamese, Lang::Finnish, Lang::Norwegian, Lang::Danish,
+    Lang::Romanian, Lang::Polish, Lang::Hungarian, Lang::Czech, Lang::Serbian, Lang::Filipino, Lang::Malay,
+    Lang::Swahili, Lang::Afrikaans, Lang::Croatian, Lang::Slovenian, Lang::Estonian, Lang::Icelandic,
+];
+
+// Smallest share (in percent) of a text for a detected language to get its stopwords
+pub const MIN_SHARE: f64 = 10.0;
+// Smallest share (in percent) of the Latin-script running words that a further Latin-script list
+// has to cover, beyond the lists already chosen, to be added
+pub const MIN_COVERAGE: f64 = 3.0;
+
+pub fn names() -> Vec<String> {
+    // Accepted --stopword-langs values
+    let mut names: Vec<String> = LANGS.iter().map(|&lang| return_lang(lang)).collect();
+    names.push("auto".to_string());
+    names.push("all".to_string());
+    names
+}
+
+fn is_latin_word(word: &str) -> bool {
+    // Basic Latin to Latin Extended-B, and Latin Extended Additional (Vietnamese)
+    word.chars().all(|c| (c as u32) < 0x250 || (0x1E00..0x1F00).contains(&(c as u32)))
+}
+
+pub fn detected_langs(lang_info: &[LangInfo], freq: &HashMap<String, usize>) -> Vec<Lang> {
+    // Languages found by lang_detect that make up at least MIN_SHARE of the text and have a list;
+    // Latin-script lists are chosen by the words of the text instead, see latin_langs
+    let mut langs: Vec<Lang> = LANGS
+        .iter()
+        .copied()
+        .filter(|lang| !LATIN.contains(lang))
+        .filter(|&lang| lang_info.iter().any(|info| info.lang == return_lang(lang) && info.percentage >= MIN_SHARE))
+        .collect();
+
+    let latin_share: f64 = lang_info
+        .iter()
+        .filter(|info| info.lang == "Accented Latin" || LATIN.iter().any(|&lang| info.lang == return_lang(lang)))
+        .map(|info| info.percentage)
+        .sum();
+    if latin_share >= MIN_SHARE {
+        langs.extend(latin_langs(freq));
+    }
+    langs
+}
+
+pub fn latin_langs(freq: &HashMap<String, usize>) -> Vec<Lang> {
+    // Greedy cover of the Latin-script running words: the list that covers most words not covered
+    // yet is added while it covers at least MIN_COVERAGE percent of them
+    let latin: Vec<(&String, usize)> = freq.iter().filter(|(w, _)| is_latin_word(w)).map(|(w, &c)| (w, c)).collect();
+    let total: usize = latin.iter().map(|(_, c)| c).sum();
+    let mut covered: HashSet<String> = HashSet::new();
+    let mut chosen = Vec::new();
+
+    loop {
+        let best = LATIN
+            .iter()
+            .filter(|lang| !chosen.contains(*lang))
+            .map(|&lang| {
+                let list = stopword_set(&[lang]);
+                let gain: usize = latin
+                    .iter()
+                    .filter(|(w, _)| list.contains(*w) && !covered.contains(*w))
+                    .map(|(_, c)| c)
+                    .sum();
+                (lang, list, gain)
+            })
+            // Ties go to the language listed first in LATIN
+            .fold(None, |best: Option<(Lang, HashSet<String>, usize)>, candidate| match best {
+                Some(b) if b.2 >= candidate.2 => Some(b),
+                _ => Some(candidate),
+            });
+        match best {
+            Some((lang, list, gain)) if total > 0 && gain as f64 / total as f64 * 100.0 >= MIN_COVERAGE => {
+                covered.extend(list);
+                chosen.push(lang);
+            }
+            _ => break,
+        }
+    }
+    chosen
+}
+
+pub fn parse_langs(spec: &str) -> Vec<Lang> {
+    // Comma-separated language names as printed in the reports ("English,German"), or "all";
+    // the command line only lets through names from names()
+    if spec.trim().eq_ignore_ascii_case("all") {
+        return LANGS.to_vec();
+    }
+    spec.split(',')
+        .map(|name| name.trim().to_lowercase())
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            *LANGS
+                .iter()
+                .find(|&&lang| return_lang(lang).to_lowercase() == name)
+                .unwrap_or_else(|| panic!("No bundled stopword list for '{}'.", name))
+        })
+        .collect()
+}
+
+fn normalize(word: &str) -> String {
+    // The same way as word_freq::tokenize
+    word.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase()
+}
+
+pub fn stopword_set(langs: &[Lang]) -> HashSet<String> {
+    langs.iter().flat_map(|&lang| bundled(lang).iter().map(|w| normalize(w))).collect()
+}
+
+pub fn is_stopword(word: &str, stopwords: &HashSet<String>) -> bool {
+    stopwords.contains(&normalize(word))
+}
+
+pub fn remove_stopwords(freq: &HashMap<String, usize>, stopwords: &HashSet<String>) -> HashMap<String, usize> {
+    freq.iter()
+        .filter(|(word, _)| !stopwords.contains(*word))
+        .map(|(word, &count)| (word.clone(), count))
+        .collect()
+}
+
+pub fn stopword_ratio(freq: &HashMap<String, usize>, stopwords: &HashSet<String>) -> f64 {
+    // Percentage of running words that are stopwords
+    let total: usize = freq.values().sum();
+    let stop: usize = freq.iter().filter(|(word, _)| stopwords.contains(*word)).map(|(_, &c)| c).sum();
+    if total == 0 { 0.0 } else { stop as f64 / total as f64 * 100.0 }
+}
+
+#[test]
+fn test_stopwords() {
+    let set = stopword_set(&parse_langs("English, German"));
+    assert!(set.contains("the") && set.contains("und"));
+    assert!(!set.contains("le"));
+    assert!(is_stopword("The,", &set));
+    assert_eq!(parse_langs("all").len(), LANGS.len());
+    assert_eq!(names().len(), LANGS.len() + 2);
+
+    let auto = |text: &str| {
+        let lines = vec![text.to_string()];
+        detected_langs(&crate::lang_detect::lang_detect(lines.clone()), &crate::word_freq::word_frequency(lines))
+    };
+    assert_eq!(auto("the cat sat on the mat and then it ran away кошка"), vec![Lang::Russian, Lang::English]);
+    // Latin-script lists are chosen by the words of the text, not by the script
+    assert_eq!(
+        auto("Der Hund und die Katze sind im Garten, und der Mann ist mit dem Kind auf dem Weg zur Schule."),
+        vec![Lang::German]
+    );
+    assert_eq!(
+        auto("Le chat est dans la maison et les enfants sont dans le jardin avec leur mère."),
+        vec![Lang::French]
+    );
+    assert_eq!(
+        auto("The dog is in the garden with the children. Der Hund ist mit den Kindern in dem Garten und spielt."),
+        vec![Lang::German, Lang::English]
+    );
+    assert!(auto("12345 67890").is_empty());
+    assert!(LANGS.iter().all(|&lang| !bundled(lang).is_empty()));
+
+    let mut freq = HashMap::new();
+    freq.insert("the".to_string(), 3);
+    freq.insert("cat".to_string(), 1);
+    assert_eq!(stopword_ratio(&freq, &set), 75.0);
+    assert_eq!(remove_stopwords(&freq, &set).len(), 1);
+}
@@ -0,0 +1,148 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::rng::Rng;
+
+// Symmetric Dirichlet priors on the document-topic and topic-word distributions
+pub const ALPHA: f64 = 0.1;
+pub const BETA: f64 = 0.01;
+
+#[derive(Debug)]
+pub struct TopicModel {
+    pub k: usize,
+    pub vocab: Vec<String>,
+    // topic_word[t][w]: tokens of word w assigned to topic t; doc_topic[d][t]: tokens of document d in topic t
+    topic_word: Vec<Vec<usize>>,
+    topic_totals: Vec<usize>,
+    doc_topic: Vec<Vec<usize>>,
+    doc_lengths: Vec<usize>,
+}
+
+pub fn lda(docs: &[Vec<String>], k: usize, iterations: usize, seed: u64) -> TopicModel {
+    // Collapsed Gibbs sampling: every token's topic is resampled from its full conditional
+    // p(z = t) ∝ (n_dt + α) (n_tw + β) / (n_t + Vβ)
+    let k = k.max(1);
+    let mut rng = Rng::new(seed);
+
+    let mut vocab: Vec<String> = docs.iter().flatten().cloned().collect::<HashSet<String>>().into_iter().collect();
+    vocab.sort();
+    let index: HashMap<&String, usize> = vocab.iter().enumerate().map(|(i, w)| (w, i)).collect();
+    let words: Vec<Vec<usize>> = docs.iter().map(|doc| doc.iter().map(|w| index[w]).collect()).collect();
+    let v = vocab.len();
+
+    let mut topic_word = vec![vec![0; v]; k];
+    let mut topic_totals = vec![0; k];
+    let mut doc_topic = vec![vec![0; k]; docs.len()];
+    let mut assignments: Vec<Vec<usize>> = Vec::with_capacity(docs.len());
+
+    // Random initial topics
+    for (d, doc) in words.iter().enumerate() {
+        let mut z = Vec::with_capacity(doc.len());
+        for &w in doc {
+            let t = rng.below(k);
+            topic_word[t][w] += 1;
+            topic_totals[t] += 1;
+            doc_topic[d][t] += 1;
+            z.push(t);
+        }
+        assignments.push(z);
+    }
+
+    let mut weights = vec![0.0; k];
+    for _ in 0..iterations {
+        for (d, doc) in words.iter().enumerate() {
+            for (i, &w) in doc.iter().enumerate() {
+                let old = assignments[d][i];
+                topic_word[old][w] -= 1;
+                topic_totals[old] -= 1;
+                doc_topic[d][old] -= 1;
+
+                let mut total = 0.0;
+                for t in 0..k {
+                    total += (doc_topic[d][t] as f64 + ALPHA) * (topic_word[t][w] as f64 + BETA)
+                        / (topic_totals[t] as f64 + v as f64 * BETA);
+                    weights[t] = total;
+                }
+                let target = rng.next_f64() * total;
+                let new = weights.iter().position(|&cumulative| target < cumulative).unwrap_or(k - 1);
+
+                topic_word[new][w] += 1;
+                topic_totals[new] += 1;
+                doc_topic[d][new] += 1;
+                assignments[d][i] = new;
+            }
+        }
+    }
+
+    let doc_lengths = words.iter().map(|doc| doc.len()).collect();
+    TopicModel { k, vocab, topic_word, topic_totals, doc_topic, doc_lengths }
+}
+
+impl TopicModel {
+    pub fn top_words(&self, topic: usize, n: usize) -> Vec<(String, f64)> {
+        // Most probable words of a topic under the smoothed estimate φ_tw
+        let v = self.vocab.len() as f64;
+        let denominator = self.topic_totals[topic] as f64 + v * BETA;
+        let mut words: Vec<(String, f64)> = self
+            .vocab
+            .iter()
+            .zip(&self.topic_word[topic])
+            .filter(|(_, &count)| count > 0)
+            .map(|(w, &count)| (w.clone(), (count as f64 + BETA) / denominator))
+            .collect();
+        words.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        words.truncate(n);
+        words
+    }
+
+    pub fn doc_mixture(&self, doc: usize) -> Vec<f64> {
+        // Smoothed topic proportions θ_d of a document
+        let denominator = self.doc_lengths[doc] as f64 + self.k as f64 * ALPHA;
+        self.doc_topic[doc].iter().map(|&count| (count as f64 + ALPHA) / denominator).collect()
+    }
+}
+
+pub fn coherence(top_words: &[String], docs: &[Vec<String>]) -> f64 {
+    // UMass coherence: sum over ranked word pairs of log((D(w_i, w_j) + 1) / D(w_j)),
+    // where D counts the documents containing the words; higher is more coherent
+    let sets: Vec<HashSet<&String>> = docs.iter().map(|doc| doc.iter().collect()).collect();
+    let doc_freq = |words: &[&String]| sets.iter().filter(|s| words.iter().all(|w| s.contains(w))).count();
+
+    let mut score = 0.0;
+    for i in 1..top_words.len() {
+        for j in 0..i {
+            let together = doc_freq(&[&top_words[i], &top_words[j]]);
+            let alone = doc_freq(&[&top_words[j]]);
+            if alone > 0 {
+                score += ((together as f64 + 1.0) / alone as f64).ln();
+            }
+        }
+    }
+    score
+}
+
+#[test]
+fn test_lda_topics() {
+    let docs: Vec<Vec<String>> = [
+        "cat dog cat pet dog cat pet",
+        "dog pet cat dog pet cat",
+        "stock bond market stock bond",
+        "market stock bond market bond stock",
+    ]
+    .iter()
+    .map(|d| d.split(' ').map(|w| w.to_string()).collect())
+    .collect();
+
+    let model = lda(&docs, 2, 200, 42);
+    let mixtures: Vec<Vec<f64>> = (0..docs.len()).map(|d| model.doc_mixture(d)).collect();
+    assert!((mixtures[0].iter().sum::<f64>() - 1.0).abs() < 1e-9);
+
+    // The pet documents and the finance documents end up dominated by different topics
+    let main_topic = |m: &Vec<f64>| if m[0] > m[1] { 0 } else { 1 };
+    assert_eq!(main_topic(&mixtures[0]), main_topic(&mixtures[1]));
+    assert_eq!(main_topic(&mixtures[2]), main_topic(&mixtures[3]));
+    assert_ne!(main_topic(&mixtures[0]), main_topic(&mixtures[2]));
+
+    let pets: Vec<String> = model.top_words(main_topic(&mixtures[0]), 3).into_iter().map(|(w, _)| w).collect();
+    assert_eq!(pets, vec!["cat", "dog", "pet"]);
+    assert!((coherence(&pets, &docs) - 3.0 * 1.5f64.ln()).abs() < 1e-12);
+}
@@ -1,16 +1,25 @@
 use std::collections::HashMap;
 
-pub fn word_frequency(lines: Vec<String>) -> HashMap<String, usize> {
-    // Word frequency analysis and HashMap construction
-    let mut word_frequencies: HashMap<String, usize> = HashMap::new();
-    for line in &lines {
+pub fn tokenize(lines: &[String]) -> Vec<String> {
+    // Lowercased words with surrounding non-alphabetic characters removed, in text order
+    let mut tokens = Vec::new();
+    for line in lines {
         for word in line.split_whitespace() {
-            if !word.is_empty() {
-                let processed = word.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase();
-                *word_frequencies.entry(processed).or_insert(0) += 1;
+            let processed = word.trim_matches(|c: char| !c.is_alphabetic()).to_lowercase();
+            if !processed.is_empty() {
+                tokens.push(processed);
             }
         }
     }
+    tokens
+}
+
+pub fn word_frequency(lines: Vec<String>) -> HashMap<String, usize> {
+    // Word frequency analysis and HashMap construction
+    let mut word_frequencies: HashMap<String, usize> = HashMap::new();
+    for word in tokenize(&lines) {
+        *word_frequencies.entry(word).or_insert(0) += 1;
+    }
     word_frequencies
 }
 
@@ -51,3 +60,14 @@ fn test_word_frequency_empty_input() {
     let expected_frequencies: HashMap<String, usize> = HashMap::new();
     assert_eq!(word_frequencies, expected_frequencies);
 }
+
+#[test]
+fn test_word_frequency_skips_empty_tokens() {
+    // Tokens without letters (numbers, punctuation) are not counted, not even as ""
+    let input_lines = vec!["42 cats -- 3.5 , cats!".to_string()];
+    let word_frequencies = word_frequency(input_lines);
+
+    let mut expected_frequencies: HashMap<String, usize> = HashMap::new();
+    expected_frequencies.insert("cats".to_string(), 2);
+    assert_eq!(word_frequencies, expected_frequencies);
+}
@@ -0,0 +1,102 @@
+use std::collections::{HashMap, HashSet};
+
+// Number of points kept on the Heaps vocabulary growth curve
+const HEAPS_POINTS: usize = 200;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PowerLawFit {
+    // y = constant * x^exponent, fitted as a straight line in log-log space
+    pub exponent: f64,
+    pub constant: f64,
+    pub r_squared: f64,
+}
+
+fn least_squares(points: &[(f64, f64)]) -> PowerLawFit {
+    // Ordinary least squares on (ln x, ln y)
+    let logs: Vec<(f64, f64)> = points
+        .iter()
+        .filter(|(x, y)| *x > 0.0 && *y > 0.0)
+        .map(|(x, y)| (x.ln(), y.ln()))
+        .collect();
+    let n = logs.len() as f64;
+    if logs.len() < 2 {
+        return PowerLawFit { exponent: 0.0, constant: 0.0, r_squared: 0.0 };
+    }
+
+    let mean_x = logs.iter().map(|p| p.0).sum::<f64>() / n;
+    let mean_y = logs.iter().map(|p| p.1).sum::<f64>() / n;
+    let sxx: f64 = logs.iter().map(|p| (p.0 - mean_x).powi(2)).sum();
+    let sxy: f64 = logs.iter().map(|p| (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let syy: f64 = logs.iter().map(|p| (p.1 - mean_y).powi(2)).sum();
+    if sxx == 0.0 {
+        return PowerLawFit { exponent: 0.0, constant: mean_y.exp(), r_squared: 0.0 };
+    }
+
+    let slope = sxy / sxx;
+    let intercept = mean_y - slope * mean_x;
+    let r_squared = if syy == 0.0 { 1.0 } else { (sxy * sxy) / (sxx * syy) };
+
+    PowerLawFit { exponent: slope, constant: intercept.exp(), r_squared }
+}
+
+pub fn rank_frequency(freq: &HashMap<String, usize>) -> Vec<(f64, f64)> {
+    // (rank, frequency) pairs, most frequent word first
+    let mut counts: Vec<usize> = freq.values().copied().collect();
+    counts.sort_unstable_by(|a, b| b.cmp(a));
+    counts
+        .iter()
+        .enumerate()
+        .map(|(i, &f)| ((i + 1) as f64, f as f64))
+        .collect()
+}
+
+pub fn zipf_fit(points: &[(f64, f64)]) -> PowerLawFit {
+    // Zipf's law: f(r) = C * r^-s, so the fitted exponent is -s
+    least_squares(points)
+}
+
+pub fn heaps_curve(tokens: &[String]) -> Vec<(f64, f64)> {
+    // (tokens read, vocabulary size) sampled at evenly spaced positions
+    let step = (tokens.len() / HEAPS_POINTS).max(1);
+    let mut seen = HashSet::new();
+    let mut curve = Vec::new();
+
+    for (i, token) in tokens.iter().enumerate() {
+        seen.insert(token.as_str());
+        if (i + 1) % step == 0 || i + 1 == tokens.len() {
+            curve.push(((i + 1) as f64, seen.len() as f64));
+        }
+    }
+
+    curve
+}
+
+pub fn heaps_fit(points: &[(f64, f64)]) -> PowerLawFit {
+    // Heaps' law: V(n) = K * n^β
+    least_squares(points)
+}
+
+#[test]
+fn test_zipf_fit() {
+    // Perfect Zipf distribution with s = 1
+    let mut freq = HashMap::new();
+    for (i, word) in ["a", "b", "c", "d", "e", "f"].iter().enumerate() {
+        freq.insert(word.to_string(), 60 / (i + 1));
+    }
+    let fit = zipf_fit(&rank_frequency(&freq));
+
+    assert!((fit.exponent + 1.0).abs() < 0.01);
+    assert!((fit.constant - 60.0).abs() < 1.0);
+    assert!(fit.r_squared > 0.99);
+}
+
+#[test]
+fn test_heaps_curve() {
+    let tokens: Vec<String> = "a b a c b d".split(' ').map(|s| s.to_string()).collect();
+    let curve = heaps_curve(&tokens);
+
+    assert_eq!(curve.len(), 6);
+    assert_eq!(curve[5], (6.0, 4.0));
+    let fit = heaps_fit(&curve);
+    assert!(fit.exponent > 0.0 && fit.exponent <= 1.0);
+}